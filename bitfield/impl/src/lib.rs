@@ -1,19 +1,49 @@
+use darling::{ast::NestedMeta, FromMeta};
 use heck::ToSnakeCase;
 use proc_macro::TokenStream;
 use proc_macro_error2::{abort, proc_macro_error};
 use quote::{format_ident, quote, quote_spanned};
 use syn::{
-    parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, Ident, ItemStruct, Lit, Meta,
-    Result, Type,
+    parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, Ident, ItemStruct, Lit,
+    LitStr, Meta, Result, Type,
 };
 
+/// Arguments of `#[bitfield(...)]`
+#[derive(Default, FromMeta)]
+#[darling(default)]
+struct BitfieldArgs {
+    /// An integer type to keep the bits in instead of a byte array, or "auto"
+    /// for the one exactly as wide as the fields
+    storage: Option<LitStr>,
+}
+
+/// Where the bits of a bitfield are kept
+enum Storage {
+    /// `[u8; N]`, the default
+    Bytes,
+    /// A single integer, with the number of bits it holds if it was named
+    /// rather than picked automatically to fit the fields exactly
+    Int {
+        ty: proc_macro2::TokenStream,
+        width: Option<usize>,
+        span: proc_macro2::Span,
+    },
+}
+
 #[proc_macro_attribute]
 #[proc_macro_error]
 pub fn bitfield(args: TokenStream, input: TokenStream) -> TokenStream {
-    let _ = args;
+    let args = match NestedMeta::parse_meta_list(args.into()) {
+        Ok(args) => args,
+        Err(e) => abort!(e.span(), "{}", e),
+    };
+    let args = match BitfieldArgs::from_list(&args) {
+        Ok(args) => args,
+        Err(e) => return e.write_errors().into(),
+    };
     let item = parse_macro_input!(input as ItemStruct);
 
-    match bitfield_impl(item) {
+    match bitfield_impl(item, args) {
         Ok(tokens) => tokens.into(),
         Err(e) => abort!(e.span(), "{}", e),
     }
@@ -22,7 +52,7 @@ pub fn bitfield(args: TokenStream, input: TokenStream) -> TokenStream {
 // Type alias to avoid clippy::type_complexity warning
 type FieldInfo<'a> = (&'a Ident, &'a Type, Option<(usize, proc_macro2::Span)>);
 
-fn bitfield_impl(item: ItemStruct) -> Result<proc_macro2::TokenStream> {
+fn bitfield_impl(item: ItemStruct, args: BitfieldArgs) -> Result<proc_macro2::TokenStream> {
     let name = &item.ident;
     let vis = &item.vis;
 
@@ -46,23 +76,15 @@ fn bitfield_impl(item: ItemStruct) -> Result<proc_macro2::TokenStream> {
         total_bits_terms.push(quote! { <#field_ty as ::bitfield::Specifier>::BITS });
     }
 
-    // Generate the size expression (total bits / 8)
-    let size_expr = if total_bits_terms.is_empty() {
+    // Generate the total bits expression
+    let total_bits = if total_bits_terms.is_empty() {
         quote! { 0 }
     } else {
         let first = &total_bits_terms[0];
         let rest = &total_bits_terms[1..];
-        quote! { (#first #(+ #rest)*) / 8 }
-    };
-
-    // Generate the mod 8 check expression
-    let total_bits_for_check = if total_bits_terms.is_empty() {
-        quote! { 0 }
-    } else {
-        let first = &total_bits_terms[0];
-        let rest = &total_bits_terms[1..];
-        quote! { (#first #(+ #rest)*) % 8 }
+        quote! { (#first #(+ #rest)*) }
     };
+    let size_expr = quote! { (#total_bits) / 8 };
 
     // Generate getters and setters
     let mut accessors = Vec::new();
@@ -111,18 +133,10 @@ fn bitfield_impl(item: ItemStruct) -> Result<proc_macro2::TokenStream> {
         bit_offset_parts.push(quote! { <#field_ty as ::bitfield::Specifier>::BITS });
     }
 
-    Ok(quote! {
-        #[repr(C)]
-        #vis struct #name {
-            data: [u8; #size_expr],
-        }
-
-        impl #name {
-            #vis fn new() -> Self {
-                Self { data: [0; #size_expr] }
-            }
-
-            fn get_bits(&self, start: usize, count: usize) -> u64 {
+    let storage = parse_storage(args.storage.as_ref(), &total_bits)?;
+    let (repr, data_ty, zero, conversions, get_bits, set_bits, check) = match storage {
+        Storage::Bytes => {
+            let get_bits = quote! {
                 let mut result: u64 = 0;
                 for i in 0..count {
                     let bit_idx = start + i;
@@ -133,9 +147,8 @@ fn bitfield_impl(item: ItemStruct) -> Result<proc_macro2::TokenStream> {
                     }
                 }
                 result
-            }
-
-            fn set_bits(&mut self, start: usize, count: usize, value: u64) {
+            };
+            let set_bits = quote! {
                 for i in 0..count {
                     let bit_idx = start + i;
                     let byte_idx = bit_idx / 8;
@@ -146,20 +159,159 @@ fn bitfield_impl(item: ItemStruct) -> Result<proc_macro2::TokenStream> {
                         self.data[byte_idx] &= !(1 << bit_in_byte);
                     }
                 }
+            };
+            // Compile-time check that total bits is multiple of 8
+            let check = quote! {
+                impl #name {
+                    const __BITS_CHECK: () = {
+                        let _ = <
+                            <::bitfield::checks::Modulo<{ #total_bits % 8 }> as ::bitfield::checks::ModuloEight>::Mod
+                            as ::bitfield::checks::TotalSizeIsMultipleOfEightBits
+                        >::CHECK;
+                    };
+                }
+            };
+            (
+                quote!(C),
+                quote!([u8; #size_expr]),
+                quote!([0; #size_expr]),
+                quote! {},
+                get_bits,
+                set_bits,
+                check,
+            )
+        }
+        Storage::Int { ty, width, span } => {
+            let conversions = quote! {
+                /// The bitfield with the given bits, the first field in the lowest ones
+                #vis fn from_bits(bits: #ty) -> Self {
+                    Self { data: bits }
+                }
+
+                /// The bits of all fields, the first field in the lowest ones
+                #vis fn into_bits(self) -> #ty {
+                    self.data
+                }
+            };
+            let get_bits = quote! {
+                let mask: #ty = <#ty>::MAX >> (<#ty>::BITS as usize - count);
+                ((self.data >> start) & mask) as u64
+            };
+            let set_bits = quote! {
+                let mask: #ty = (<#ty>::MAX >> (<#ty>::BITS as usize - count)) << start;
+                self.data = (self.data & !mask) | (((value as #ty) << start) & mask);
+            };
+            // A named integer only has to be wide enough, the automatic one is
+            // picked to fit exactly
+            let check = match width {
+                Some(width) => {
+                    let message = format!(
+                        "the fields of {} take more than the {} bits of its storage",
+                        name, width
+                    );
+                    quote_spanned! {span=>
+                        const _: () = ::core::assert!(#total_bits <= #width, #message);
+                    }
+                }
+                None => {
+                    let message = format!(
+                        "the fields of {} must take 8, 16, 32, 64 or 128 bits for \
+                         storage = \"auto\"",
+                        name
+                    );
+                    quote_spanned! {span=>
+                        const _: () = ::core::assert!(
+                            #total_bits == ::bitfield::checks::integer_width(#total_bits),
+                            #message
+                        );
+                    }
+                }
+            };
+            (
+                quote!(transparent),
+                ty,
+                quote!(0),
+                conversions,
+                get_bits,
+                set_bits,
+                check,
+            )
+        }
+    };
+
+    Ok(quote! {
+        #[repr(#repr)]
+        #vis struct #name {
+            data: #data_ty,
+        }
+
+        impl #name {
+            #vis fn new() -> Self {
+                Self { data: #zero }
+            }
+
+            #conversions
+
+            #[inline]
+            fn get_bits(&self, start: usize, count: usize) -> u64 {
+                #get_bits
+            }
+
+            #[inline]
+            fn set_bits(&mut self, start: usize, count: usize, value: u64) {
+                #set_bits
             }
 
             #(#accessors)*
         }
 
-        // Compile-time check that total bits is multiple of 8
-        impl #name {
-            const __BITS_CHECK: () = {
-                let _ = <
-                    <::bitfield::checks::Modulo<{ #total_bits_for_check }> as ::bitfield::checks::ModuloEight>::Mod
-                    as ::bitfield::checks::TotalSizeIsMultipleOfEightBits
-                >::CHECK;
+        #check
+    })
+}
+
+/// The storage asked for by `storage = "..."`
+fn parse_storage(
+    storage: Option<&LitStr>,
+    total_bits: &proc_macro2::TokenStream,
+) -> Result<Storage> {
+    let Some(lit) = storage else {
+        return Ok(Storage::Bytes);
+    };
+    let span = lit.span();
+    let width = match lit.value().as_str() {
+        "auto" => {
+            // Rounded up, so that the type resolves even when the fields don't
+            // fill it and the check reports that once
+            let ty = quote_spanned! {span=>
+                <::bitfield::checks::TotalBits<{ ::bitfield::checks::integer_width(#total_bits) }> as ::bitfield::checks::IntegerStorage>::Int
             };
+            return Ok(Storage::Int {
+                ty,
+                width: None,
+                span,
+            });
         }
+        "u8" => 8,
+        "u16" => 16,
+        "u32" => 32,
+        "u64" => 64,
+        "u128" => 128,
+        other => {
+            return Err(Error::new(
+                span,
+                format!(
+                    "unsupported bitfield storage `{}`, expected \"u8\", \"u16\", \"u32\", \
+                     \"u64\", \"u128\" or \"auto\"",
+                    other
+                ),
+            ));
+        }
+    };
+    let ty = format_ident!("{}", lit.value(), span = span);
+    Ok(Storage::Int {
+        ty: quote!(#ty),
+        width: Some(width),
+        span,
     })
 }
 
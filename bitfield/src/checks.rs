@@ -72,3 +72,40 @@ impl GetBoolType for CheckDiscriminantInRange<true> {
 impl GetBoolType for CheckDiscriminantInRange<false> {
     type Type = False;
 }
+
+// Integer storage picked by size, for #[bitfield(storage = "auto")]
+pub struct TotalBits<const N: usize>;
+
+/// The width of the narrowest unsigned integer holding `bits` bits, or of the
+/// widest one if none does. That the fields fill it exactly is checked on its
+/// own, so that the storage type resolves either way.
+pub const fn integer_width(bits: usize) -> usize {
+    match bits {
+        0..=8 => 8,
+        9..=16 => 16,
+        17..=32 => 32,
+        33..=64 => 64,
+        _ => 128,
+    }
+}
+
+/// Implemented for the sizes of the unsigned integers
+pub trait IntegerStorage {
+    type Int;
+}
+
+impl IntegerStorage for TotalBits<8> {
+    type Int = u8;
+}
+impl IntegerStorage for TotalBits<16> {
+    type Int = u16;
+}
+impl IntegerStorage for TotalBits<32> {
+    type Int = u32;
+}
+impl IntegerStorage for TotalBits<64> {
+    type Int = u64;
+}
+impl IntegerStorage for TotalBits<128> {
+    type Int = u128;
+}
@@ -0,0 +1,96 @@
+// With `#[bitfield(storage = "u32")]` the bits are kept in a single integer
+// instead of a byte array, and the accessors mask and shift that integer. The
+// struct is `#[repr(transparent)]`, so it can stand in for the integer in FFI
+// declarations, and `from_bits`/`into_bits` convert between the two.
+//
+// `storage = "auto"` picks the integer exactly as wide as the fields, which has
+// to be 8, 16, 32, 64 or 128 bits. A named integer only has to be wide enough,
+// leaving the unused high bits zero.
+
+use bitfield::*;
+use std::mem::{align_of, size_of};
+
+#[bitfield(storage = "u32")]
+pub struct Header {
+    version: B4,
+    ihl: B4,
+    dscp: B6,
+    ecn: B2,
+    total_length: B16,
+}
+
+#[bitfield(storage = "u64")]
+pub struct Sparse {
+    flag: bool,
+    value: B20,
+}
+
+#[bitfield(storage = "auto")]
+pub struct Wide {
+    low: B64,
+    middle: B60,
+    high: B4,
+}
+
+#[bitfield(storage = "auto")]
+pub struct Flags {
+    mode: Mode,
+    enabled: bool,
+    count: B5,
+}
+
+#[derive(BitfieldSpecifier, Debug, PartialEq)]
+pub enum Mode {
+    Off = 0,
+    On = 1,
+    Auto = 2,
+    Manual = 3,
+}
+
+fn main() {
+    assert_eq!(size_of::<Header>(), size_of::<u32>());
+    assert_eq!(align_of::<Header>(), align_of::<u32>());
+    assert_eq!(size_of::<Sparse>(), size_of::<u64>());
+    assert_eq!(size_of::<Wide>(), size_of::<u128>());
+    assert_eq!(size_of::<Flags>(), size_of::<u8>());
+
+    let mut header = Header::new();
+    header.set_version(4);
+    header.set_ihl(5);
+    header.set_ecn(0b11);
+    header.set_total_length(0xABCD);
+    assert_eq!(header.get_version(), 4);
+    assert_eq!(header.get_ihl(), 5);
+    assert_eq!(header.get_dscp(), 0);
+    assert_eq!(header.get_ecn(), 0b11);
+    assert_eq!(header.get_total_length(), 0xABCD);
+
+    // The first field is in the lowest bits
+    let bits = header.into_bits();
+    assert_eq!(bits, 0xABCD_C054);
+    let header = Header::from_bits(bits);
+    assert_eq!(header.get_total_length(), 0xABCD);
+
+    // Setting a field leaves its neighbours alone
+    let mut sparse = Sparse::from_bits(u64::MAX);
+    sparse.set_flag(false);
+    sparse.set_value(0);
+    assert_eq!(sparse.into_bits(), u64::MAX << 21);
+
+    let mut wide = Wide::new();
+    wide.set_low(u64::MAX);
+    wide.set_middle(0x0123_4567_89AB_CDEF);
+    wide.set_high(0b1010);
+    assert_eq!(wide.get_low(), u64::MAX);
+    assert_eq!(wide.get_middle(), 0x0123_4567_89AB_CDEF);
+    assert_eq!(wide.get_high(), 0b1010);
+
+    let mut flags = Flags::new();
+    flags.set_mode(Mode::Manual);
+    flags.set_enabled(true);
+    flags.set_count(31);
+    assert_eq!(flags.get_mode(), Mode::Manual);
+    assert!(flags.get_enabled());
+    assert_eq!(flags.get_count(), 31);
+    assert_eq!(flags.into_bits(), 0xFF);
+}
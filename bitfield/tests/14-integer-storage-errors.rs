@@ -0,0 +1,24 @@
+// The compile_fail counterpart of the previous test. A named storage integer
+// must hold all the fields, "auto" needs fields adding up to the width of an
+// integer, and only the unsigned integers are accepted as storage.
+
+use bitfield::*;
+
+#[bitfield(storage = "u8")]
+pub struct TooWide {
+    a: B4,
+    b: B5,
+}
+
+#[bitfield(storage = "auto")]
+pub struct NoInteger {
+    a: B16,
+    b: B8,
+}
+
+#[bitfield(storage = "i32")]
+pub struct Signed {
+    a: B32,
+}
+
+fn main() {}
@@ -13,4 +13,6 @@ fn tests() {
     t.pass("tests/10-bits-attribute.rs");
     t.compile_fail("tests/11-bits-attribute-wrong.rs");
     t.pass("tests/12-accessors-edge.rs");
+    t.pass("tests/13-integer-storage.rs");
+    t.compile_fail("tests/14-integer-storage-errors.rs");
 }
@@ -0,0 +1,627 @@
+use darling::{ast::Data, FromDeriveInput, FromField};
+use proc_macro::TokenStream;
+use proc_macro_error2::{abort, proc_macro_error};
+use quote::{format_ident, quote};
+use std::collections::HashSet;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, DeriveInput, GenericArgument, Ident, Lit, Meta,
+    PathArguments, Type, TypePath, WherePredicate,
+};
+
+/// Field information parsed by darling
+#[derive(FromField)]
+#[darling(forward_attrs(debug))]
+struct DebugField {
+    ident: Option<Ident>,
+    ty: Type,
+    attrs: Vec<Attribute>,
+}
+
+impl DebugField {
+    /// Get the custom format from #[debug = "..."] attribute
+    fn get_format(&self) -> Option<String> {
+        for attr in &self.attrs {
+            if !attr.path().is_ident("debug") {
+                continue;
+            }
+            // Handle #[debug = "..."] format (MetaNameValue)
+            if let Meta::NameValue(nv) = &attr.meta {
+                if let syn::Expr::Lit(syn::ExprLit {
+                    lit: Lit::Str(lit_str),
+                    ..
+                }) = &nv.value
+                {
+                    return Some(lit_str.value());
+                }
+            }
+        }
+        None
+    }
+
+    /// Get the options from #[debug(...)] list attributes
+    fn get_options(&self) -> FieldOptions {
+        let mut options = FieldOptions::default();
+        for attr in &self.attrs {
+            if !attr.path().is_ident("debug") || !matches!(attr.meta, Meta::List(_)) {
+                continue;
+            }
+            let result = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip_if") {
+                    let lit: syn::LitStr = meta.value()?.parse()?;
+                    options.skip_if = Some(lit.parse()?);
+                    return Ok(());
+                }
+                let Some(preset) = BytesPreset::from_path(&meta.path) else {
+                    return Err(meta.error("unrecognized debug attribute"));
+                };
+                if options.preset.is_some() {
+                    return Err(
+                        meta.error("only one of hex, hexdump, base64 or utf8_lossy may be used")
+                    );
+                }
+                if self.get_format().is_some() {
+                    return Err(meta.error("cannot combine a byte preset with #[debug = \"...\"]"));
+                }
+                options.preset = Some(preset);
+                Ok(())
+            });
+            if let Err(e) = result {
+                abort!(e.span(), "{}", e);
+            }
+        }
+        options
+    }
+}
+
+/// Field-level options from #[debug(...)]
+#[derive(Default)]
+struct FieldOptions {
+    /// Predicate from #[debug(skip_if = "...")], e.g. `Option::is_none`
+    skip_if: Option<syn::ExprPath>,
+    /// Byte formatting preset, e.g. #[debug(hex)]
+    preset: Option<BytesPreset>,
+}
+
+/// Built-in formats for byte fields, backed by wrappers in `derive_debug::bytes`
+enum BytesPreset {
+    Hex,
+    HexDump,
+    Base64,
+    Utf8Lossy,
+}
+
+impl BytesPreset {
+    fn from_path(path: &syn::Path) -> Option<Self> {
+        let ident = path.get_ident()?;
+        match ident.to_string().as_str() {
+            "hex" => Some(BytesPreset::Hex),
+            "hexdump" => Some(BytesPreset::HexDump),
+            "base64" => Some(BytesPreset::Base64),
+            "utf8_lossy" => Some(BytesPreset::Utf8Lossy),
+            _ => None,
+        }
+    }
+
+    /// Name of the wrapper type in `derive_debug::bytes`
+    fn wrapper(&self) -> Ident {
+        let name = match self {
+            BytesPreset::Hex => "Hex",
+            BytesPreset::HexDump => "HexDump",
+            BytesPreset::Base64 => "Base64",
+            BytesPreset::Utf8Lossy => "Utf8Lossy",
+        };
+        format_ident!("{}", name)
+    }
+}
+
+/// Derive input parsed by darling
+#[derive(FromDeriveInput)]
+#[darling(supports(struct_named), attributes(debug), forward_attrs(repr))]
+struct DebugInput {
+    ident: Ident,
+    generics: syn::Generics,
+    data: Data<(), DebugField>,
+    attrs: Vec<Attribute>,
+    /// Struct-level bound from #[debug(bound = "...")] - parsed automatically by darling
+    #[darling(default)]
+    bound: Option<String>,
+    /// Struct-level #[debug(non_exhaustive)] - finish with `..` when a field was skipped
+    #[darling(default)]
+    non_exhaustive: bool,
+}
+
+impl DebugInput {
+    /// Whether the struct is #[repr(packed)], whose fields cannot be borrowed
+    fn is_packed(&self) -> bool {
+        is_packed(&self.attrs)
+    }
+}
+
+/// Union input parsed by darling; darling cannot read union bodies so the
+/// fields are taken from the syn `DataUnion` instead
+#[derive(FromDeriveInput)]
+#[darling(attributes(debug), forward_attrs(repr))]
+struct DebugUnionInput {
+    ident: Ident,
+    generics: syn::Generics,
+    attrs: Vec<Attribute>,
+    /// Union-level #[debug(union_as = "...")] - field to interpret the union as,
+    /// a promise by the user that the field is always initialized
+    #[darling(default)]
+    union_as: Option<Ident>,
+    /// Union-level #[debug(unsafe_raw_bytes)] - print the bytes of the union, a
+    /// promise by the user that every byte is always initialized
+    #[darling(default)]
+    unsafe_raw_bytes: bool,
+}
+
+#[proc_macro_derive(CustomDebug, attributes(debug))]
+#[proc_macro_error]
+pub fn derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    if let syn::Data::Union(data) = &input.data {
+        let parsed = DebugUnionInput::from_derive_input(&input)
+            .unwrap_or_else(|e| abort!(e.span(), "{}", e));
+        return derive_union_impl(parsed, data).into();
+    }
+
+    let parsed =
+        DebugInput::from_derive_input(&input).unwrap_or_else(|e| abort!(e.span(), "{}", e));
+    derive_debug_impl(parsed).into()
+}
+
+#[proc_macro_derive(DebugFields, attributes(debug))]
+#[proc_macro_error]
+pub fn derive_fields(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    if let syn::Data::Union(data) = &input.data {
+        abort!(data.union_token, "DebugFields does not support unions");
+    }
+
+    let parsed =
+        DebugInput::from_derive_input(&input).unwrap_or_else(|e| abort!(e.span(), "{}", e));
+    derive_fields_impl(parsed).into()
+}
+
+fn derive_debug_impl(input: DebugInput) -> proc_macro2::TokenStream {
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let non_exhaustive = input.non_exhaustive;
+    let packed = input.is_packed();
+    let fields = struct_fields(&input);
+    let bindings = packed_field_bindings(fields, packed);
+
+    // Collect field debug calls
+    let mut field_debug_calls = Vec::new();
+
+    // Whether any field carries #[debug(skip_if = "...")]
+    let mut has_skip_if = false;
+
+    for field in fields {
+        let field_name = field.ident.as_ref().unwrap();
+        let field_name_str = field_name.to_string();
+        let access = field_access(field, packed);
+        let value = field_value(field, &access);
+
+        // Generate field debug call
+        let field_call = quote! {
+            debug.field(#field_name_str, #value);
+        };
+
+        // Wrap in the #[debug(skip_if = "...")] predicate if present
+        if let Some(skip_if) = field.get_options().skip_if {
+            has_skip_if = true;
+            let mark_skipped = if non_exhaustive {
+                quote! { else { skipped = true; } }
+            } else {
+                quote! {}
+            };
+            field_debug_calls.push(quote! {
+                if !#skip_if(&#access) {
+                    #field_call
+                } #mark_skipped
+            });
+        } else {
+            field_debug_calls.push(field_call);
+        }
+    }
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let where_clause = debug_where_clause(&input, fields);
+
+    // Only #[debug(non_exhaustive)] structs show `..` for skipped fields
+    let finish = if non_exhaustive && has_skip_if {
+        quote! {
+            let mut skipped = false;
+            #(#field_debug_calls)*
+            if skipped {
+                debug.finish_non_exhaustive()
+            } else {
+                debug.finish()
+            }
+        }
+    } else {
+        quote! {
+            #(#field_debug_calls)*
+            debug.finish()
+        }
+    };
+
+    quote! {
+        impl #impl_generics ::std::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                #bindings
+                let mut debug = f.debug_struct(#name_str);
+                #finish
+            }
+        }
+    }
+}
+
+fn derive_fields_impl(input: DebugInput) -> proc_macro2::TokenStream {
+    let name = &input.ident;
+    let packed = input.is_packed();
+    let fields = struct_fields(&input);
+    let bindings = packed_field_bindings(fields, packed);
+
+    // Same field selection and formatting as CustomDebug, one visit per field
+    let field_visits = fields.iter().map(|field| {
+        let field_name_str = field.ident.as_ref().unwrap().to_string();
+        let access = field_access(field, packed);
+        let value = field_value(field, &access);
+
+        let visit = quote! {
+            visitor.visit_field(#field_name_str, #value);
+        };
+        match field.get_options().skip_if {
+            Some(skip_if) => quote! {
+                if !#skip_if(&#access) {
+                    #visit
+                }
+            },
+            None => visit,
+        }
+    });
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let where_clause = debug_where_clause(&input, fields);
+
+    quote! {
+        impl #impl_generics ::derive_debug::DebugFields for #name #ty_generics #where_clause {
+            fn visit_fields(&self, visitor: &mut dyn ::derive_debug::FieldVisitor) {
+                #bindings
+                #(#field_visits)*
+            }
+        }
+    }
+}
+
+/// Get the named fields of the struct being derived
+fn struct_fields(input: &DebugInput) -> &[DebugField] {
+    match &input.data {
+        Data::Struct(fields) => &fields.fields,
+        Data::Enum(_) => unreachable!("Only structs are supported"),
+    }
+}
+
+/// Expression naming a field: `self.field`, or its local copy when packed
+fn field_access(field: &DebugField, packed: bool) -> proc_macro2::TokenStream {
+    let field_name = field.ident.as_ref().unwrap();
+    if packed {
+        let local = format_ident!("__self_{}", field_name);
+        quote! { #local }
+    } else {
+        quote! { self.#field_name }
+    }
+}
+
+/// Copy every field of a packed struct into a local, since borrowing a
+/// possibly unaligned field is not allowed
+fn packed_field_bindings(fields: &[DebugField], packed: bool) -> proc_macro2::TokenStream {
+    if !packed {
+        return quote! {};
+    }
+    let bindings = fields.iter().map(|field| {
+        let field_name = field.ident.as_ref().unwrap();
+        let local = field_access(field, packed);
+        quote! { let #local = { self.#field_name }; }
+    });
+    quote! { #(#bindings)* }
+}
+
+/// Expression borrowing a field as `&dyn Debug`, honoring #[debug = "..."]
+/// and the byte presets
+fn field_value(field: &DebugField, access: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    if let Some(preset) = field.get_options().preset {
+        let wrapper = preset.wrapper();
+        quote! {
+            &::derive_debug::bytes::#wrapper(::std::convert::AsRef::<[u8]>::as_ref(&#access))
+        }
+    } else if let Some(fmt) = field.get_format() {
+        quote! { &::std::format_args!(#fmt, &#access) }
+    } else {
+        quote! { &#access }
+    }
+}
+
+fn derive_union_impl(input: DebugUnionInput, data: &syn::DataUnion) -> proc_macro2::TokenStream {
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut where_predicates: Vec<WherePredicate> = where_clause
+        .map(|wc| wc.predicates.iter().cloned().collect())
+        .unwrap_or_default();
+
+    if let (Some(union_as), true) = (&input.union_as, input.unsafe_raw_bytes) {
+        abort!(
+            union_as,
+            "#[debug(union_as = \"...\")] and #[debug(unsafe_raw_bytes)] cannot be combined"
+        );
+    }
+
+    let body = if let Some(union_as) = &input.union_as {
+        // Print the union as the user-selected field
+        let Some(field) = data
+            .fields
+            .named
+            .iter()
+            .find(|f| f.ident.as_ref() == Some(union_as))
+        else {
+            abort!(union_as, "no field `{}` in union `{}`", union_as, name);
+        };
+        let field_ty = &field.ty;
+        let field_name_str = union_as.to_string();
+        where_predicates.push(parse_quote!(#field_ty: ::std::fmt::Debug));
+
+        // Copy out of packed unions rather than borrowing an unaligned field
+        let value = if is_packed(&input.attrs) {
+            quote! { let value = unsafe { self.#union_as }; }
+        } else {
+            quote! { let value = unsafe { &self.#union_as }; }
+        };
+        quote! {
+            #value
+            f.debug_struct(#name_str)
+                .field(#field_name_str, &value)
+                .finish()
+        }
+    } else if input.unsafe_raw_bytes {
+        // Reading bytes that were never written is undefined behavior; the
+        // attribute is the user's promise that every byte is initialized, e.g.
+        // because each value is built through its largest field
+        quote! {
+            let bytes: &[u8] = unsafe {
+                ::std::slice::from_raw_parts(
+                    self as *const Self as *const u8,
+                    ::std::mem::size_of::<Self>(),
+                )
+            };
+            f.debug_struct(#name_str)
+                .field("bytes", &bytes)
+                .finish()
+        }
+    } else {
+        // Which field is active is not known, and reading the wrong one or a
+        // byte no field wrote would be undefined behavior in safe code
+        quote! {
+            f.debug_struct(#name_str).finish_non_exhaustive()
+        }
+    };
+
+    let where_clause = if where_predicates.is_empty() {
+        quote! {}
+    } else {
+        quote! { where #(#where_predicates),* }
+    };
+
+    quote! {
+        impl #impl_generics ::std::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                #body
+            }
+        }
+    }
+}
+
+/// Check for #[repr(packed)] or #[repr(packed(N))] among the forwarded attributes
+fn is_packed(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+        .filter_map(|attr| attr.meta.require_list().ok())
+        .flat_map(|list| list.tokens.clone())
+        .any(|tt| matches!(tt, proc_macro2::TokenTree::Ident(ident) if ident == "packed"))
+}
+
+/// Build the where clause with the inferred (or user-provided) Debug bounds
+fn debug_where_clause(input: &DebugInput, fields: &[DebugField]) -> proc_macro2::TokenStream {
+    let custom_bound = &input.bound;
+
+    // Collect type parameters
+    let type_params: Vec<&Ident> = input.generics.type_params().map(|p| &p.ident).collect();
+
+    // Track which type parameters are ONLY used in PhantomData
+    let mut phantom_only_params: HashSet<String> =
+        type_params.iter().map(|p| p.to_string()).collect();
+
+    // Track which type parameters are used via associated types
+    let mut associated_type_params: HashSet<String> = HashSet::new();
+
+    // Track associated type bounds needed
+    let mut associated_type_bounds: Vec<proc_macro2::TokenStream> = Vec::new();
+
+    // Fields with a byte preset are formatted through `AsRef<[u8]>`, not Debug
+    let mut byte_field_types: Vec<&Type> = Vec::new();
+
+    // Only infer bounds if no custom bound is specified
+    if custom_bound.is_none() {
+        for field in fields {
+            if field.get_options().preset.is_some() {
+                byte_field_types.push(&field.ty);
+                continue;
+            }
+            analyze_type_for_bounds(
+                &field.ty,
+                &type_params,
+                &mut phantom_only_params,
+                &mut associated_type_params,
+                &mut associated_type_bounds,
+            );
+        }
+    }
+
+    // Build where predicates
+    let mut where_predicates: Vec<WherePredicate> = Vec::new();
+
+    // Add existing where predicates
+    if let Some(wc) = &input.generics.where_clause {
+        where_predicates.extend(wc.predicates.iter().cloned());
+    }
+
+    if let Some(bound_str) = custom_bound {
+        // Parse and add custom bound
+        let bound: WherePredicate =
+            syn::parse_str(bound_str).expect("failed to parse custom bound");
+        where_predicates.push(bound);
+    } else {
+        // Add Debug bounds for type parameters that need them
+        for param in type_params.iter() {
+            let param_str = param.to_string();
+
+            // Skip if only used in PhantomData
+            if phantom_only_params.contains(&param_str) {
+                continue;
+            }
+
+            // Skip if only used via associated types
+            if associated_type_params.contains(&param_str) {
+                continue;
+            }
+
+            // Otherwise, add Debug bound
+            where_predicates.push(parse_quote!(#param: ::std::fmt::Debug));
+        }
+
+        // Add associated type bounds
+        for bound in associated_type_bounds {
+            let predicate: WherePredicate = syn::parse2(quote! { #bound: ::std::fmt::Debug })
+                .expect("failed to parse associated type bound");
+            where_predicates.push(predicate);
+        }
+
+        for ty in byte_field_types {
+            where_predicates.push(parse_quote!(#ty: ::std::convert::AsRef<[u8]>));
+        }
+    }
+
+    // Build the where clause
+    if where_predicates.is_empty() {
+        quote! {}
+    } else {
+        quote! { where #(#where_predicates),* }
+    }
+}
+
+/// Analyzes a type to determine what bounds are needed
+fn analyze_type_for_bounds(
+    ty: &Type,
+    type_params: &[&Ident],
+    phantom_only_params: &mut HashSet<String>,
+    associated_type_params: &mut HashSet<String>,
+    associated_type_bounds: &mut Vec<proc_macro2::TokenStream>,
+) {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        // Handle other type variants recursively
+        match ty {
+            Type::Reference(type_ref) => {
+                analyze_type_for_bounds(
+                    &type_ref.elem,
+                    type_params,
+                    phantom_only_params,
+                    associated_type_params,
+                    associated_type_bounds,
+                );
+            }
+            Type::Tuple(type_tuple) => {
+                for elem in &type_tuple.elems {
+                    analyze_type_for_bounds(
+                        elem,
+                        type_params,
+                        phantom_only_params,
+                        associated_type_params,
+                        associated_type_bounds,
+                    );
+                }
+            }
+            Type::Array(type_array) => {
+                analyze_type_for_bounds(
+                    &type_array.elem,
+                    type_params,
+                    phantom_only_params,
+                    associated_type_params,
+                    associated_type_bounds,
+                );
+            }
+            Type::Slice(type_slice) => {
+                analyze_type_for_bounds(
+                    &type_slice.elem,
+                    type_params,
+                    phantom_only_params,
+                    associated_type_params,
+                    associated_type_bounds,
+                );
+            }
+            _ => {}
+        }
+        return;
+    };
+
+    let segments = &path.segments;
+
+    // Check if this is PhantomData<T>
+    if segments.len() == 1 && segments[0].ident == "PhantomData" {
+        return; // PhantomData doesn't require Debug bound
+    }
+
+    // Check if first segment is a type parameter (associated type case like T::Value)
+    if segments.len() > 1 {
+        let first_segment = &segments[0];
+        if let Some(param) = type_params.iter().find(|p| **p == &first_segment.ident) {
+            let param_str = param.to_string();
+            associated_type_params.insert(param_str.clone());
+            phantom_only_params.remove(&param_str);
+            associated_type_bounds.push(quote! { #path });
+            return;
+        }
+    }
+
+    // Check if this type directly is a type parameter
+    if segments.len() == 1 {
+        let segment = &segments[0];
+        if let Some(param) = type_params.iter().find(|p| **p == &segment.ident) {
+            phantom_only_params.remove(&param.to_string());
+            return;
+        }
+    }
+
+    // Recurse into generic arguments (e.g., Vec<T>, Option<T>)
+    for segment in segments {
+        let PathArguments::AngleBracketed(args) = &segment.arguments else {
+            continue;
+        };
+        for arg in &args.args {
+            let GenericArgument::Type(inner_ty) = arg else {
+                continue;
+            };
+            analyze_type_for_bounds(
+                inner_ty,
+                type_params,
+                phantom_only_params,
+                associated_type_params,
+                associated_type_bounds,
+            );
+        }
+    }
+}
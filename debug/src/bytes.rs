@@ -0,0 +1,87 @@
+// Debug wrappers for byte fields, used by the #[debug(hex)], #[debug(hexdump)],
+// #[debug(base64)] and #[debug(utf8_lossy)] presets.
+
+use std::fmt::{self, Debug, Write};
+
+/// Contiguous lowercase hex, e.g. `0a1bff`
+pub struct Hex<'a>(pub &'a [u8]);
+
+impl Debug for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Classic `hexdump -C` layout: offset, 16 bytes of hex, then printable ASCII
+pub struct HexDump<'a>(pub &'a [u8]);
+
+impl Debug for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (line, chunk) in self.0.chunks(16).enumerate() {
+            if line > 0 {
+                f.write_char('\n')?;
+            }
+            write!(f, "{:08x} ", line * 16)?;
+            for i in 0..16 {
+                if i == 8 {
+                    f.write_char(' ')?;
+                }
+                match chunk.get(i) {
+                    Some(byte) => write!(f, " {:02x}", byte)?,
+                    None => f.write_str("   ")?,
+                }
+            }
+            f.write_str("  |")?;
+            for &byte in chunk {
+                let c = if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                };
+                f.write_char(c)?;
+            }
+            f.write_char('|')?;
+        }
+        Ok(())
+    }
+}
+
+/// Standard base64 alphabet with padding
+pub struct Base64<'a>(pub &'a [u8]);
+
+impl Debug for Base64<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const ALPHABET: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+        for chunk in self.0.chunks(3) {
+            let b = [
+                chunk[0],
+                chunk.get(1).copied().unwrap_or(0),
+                chunk.get(2).copied().unwrap_or(0),
+            ];
+            let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    let index = (n >> (18 - 6 * i)) & 0x3f;
+                    f.write_char(ALPHABET[index as usize] as char)?;
+                } else {
+                    f.write_char('=')?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Quoted string with invalid UTF-8 replaced by U+FFFD
+pub struct Utf8Lossy<'a>(pub &'a [u8]);
+
+impl Debug for Utf8Lossy<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(&String::from_utf8_lossy(self.0), f)
+    }
+}
@@ -0,0 +1,57 @@
+// Structs with many optional fields are hard to read when every `None` gets
+// printed. Support a field attribute #[debug(skip_if = "...")] naming a
+// predicate that receives a reference to the field; when it returns true the
+// field is left out of the output.
+//
+// A struct-level #[debug(non_exhaustive)] attribute additionally makes the
+// output end with `..` whenever at least one field was skipped, mirroring
+// `DebugStruct::finish_non_exhaustive`.
+//
+//     Sparse { id: 1, .. }
+//
+// Resources:
+//
+//   - Method used to end the output with `..`:
+//     https://doc.rust-lang.org/std/fmt/struct.DebugStruct.html#method.finish_non_exhaustive
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(non_exhaustive)]
+pub struct Sparse {
+    id: u32,
+    #[debug(skip_if = "Option::is_none")]
+    name: Option<&'static str>,
+    #[debug(skip_if = "Option::is_none")]
+    #[debug = "{:02x?}"]
+    flags: Option<u8>,
+}
+
+#[derive(CustomDebug)]
+pub struct Quiet {
+    id: u32,
+    #[debug(skip_if = "Vec::is_empty")]
+    tags: Vec<&'static str>,
+}
+
+fn main() {
+    let all = Sparse {
+        id: 1,
+        name: Some("one"),
+        flags: Some(3),
+    };
+    assert_eq!(
+        format!("{:?}", all),
+        r#"Sparse { id: 1, name: Some("one"), flags: Some(03) }"#
+    );
+
+    let sparse = Sparse {
+        id: 1,
+        name: None,
+        flags: None,
+    };
+    assert_eq!(format!("{:?}", sparse), "Sparse { id: 1, .. }");
+
+    let quiet = Quiet { id: 2, tags: vec![] };
+    assert_eq!(format!("{:?}", quiet), "Quiet { id: 2 }");
+}
@@ -0,0 +1,71 @@
+// Logging pipelines often want structured key/value pairs rather than a single
+// formatted string. Add a second derive, DebugFields, which implements the
+// derive_debug::DebugFields trait by handing each field to a FieldVisitor as a
+// `(name, &dyn Debug)` pair.
+//
+// The derive should behave exactly like CustomDebug as far as fields go: the
+// #[debug = "..."] format is applied, #[debug(skip_if = "...")] fields are left
+// out when the predicate holds, and the same trait bounds are inferred.
+//
+// Since a proc-macro crate can only export macros, the traits live in the
+// derive_debug crate while the derives move into a derive_debug-impl crate and
+// are re-exported, the same way the bitfield project is laid out.
+
+use derive_debug::{CustomDebug, DebugFields, FieldVisitor};
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+#[derive(CustomDebug, DebugFields)]
+pub struct Request<T> {
+    method: &'static str,
+    #[debug = "0b{:04b}"]
+    flags: u8,
+    #[debug(skip_if = "Option::is_none")]
+    body: Option<T>,
+    marker: PhantomData<T>,
+}
+
+#[derive(DebugFields)]
+pub struct Tagged<T> {
+    id: u32,
+    tag: PhantomData<T>,
+}
+
+struct Collect(Vec<String>);
+
+impl FieldVisitor for Collect {
+    fn visit_field(&mut self, name: &'static str, value: &dyn Debug) {
+        self.0.push(format!("{}={:?}", name, value));
+    }
+}
+
+fn assert_debug_fields<F: DebugFields>() {}
+
+fn main() {
+    // PhantomData<T> alone does not require T: Debug.
+    struct NotDebug;
+    assert_debug_fields::<Tagged<NotDebug>>();
+
+    let request = Request {
+        method: "GET",
+        flags: 5,
+        body: None::<u32>,
+        marker: PhantomData,
+    };
+
+    let mut collect = Collect(Vec::new());
+    request.visit_fields(&mut collect);
+    assert_eq!(
+        collect.0,
+        ["method=\"GET\"", "flags=0b0101", "marker=PhantomData<u32>"],
+    );
+
+    // Closures work as visitors too.
+    let mut names = Vec::new();
+    Request {
+        body: Some(1u32),
+        ..request
+    }
+    .visit_fields(&mut |name: &'static str, _: &dyn Debug| names.push(name));
+    assert_eq!(names, ["method", "flags", "body", "marker"]);
+}
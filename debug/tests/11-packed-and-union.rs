@@ -0,0 +1,72 @@
+// Taking a reference to a field of a #[repr(packed)] struct is an error, since
+// the field may not be properly aligned. When the struct is packed, copy each
+// field into a local variable first and format the local instead.
+//
+// Unions cannot be formatted field by field because only one field is valid at
+// a time, and reading one that was not written is undefined behavior. By default
+// print just the union's name. With a union-level #[debug(union_as = "...")]
+// attribute, print it as the named field instead; with #[debug(unsafe_raw_bytes)]
+// print its raw bytes. Both are the user's promise that what gets read is always
+// initialized, since the Debug impl cannot check it.
+//
+//     Word { .. }
+//     Bytes { bytes: [1, 0, 0, 0] }
+//     Number { int: 7 }
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug, Clone, Copy)]
+#[repr(C, packed)]
+pub struct Header {
+    tag: u8,
+    #[debug = "0x{:08x}"]
+    length: u32,
+    #[debug(skip_if = "Option::is_none")]
+    checksum: Option<u16>,
+}
+
+#[derive(CustomDebug, Clone, Copy)]
+#[repr(C)]
+pub union Word {
+    int: u32,
+    byte: u8,
+}
+
+#[derive(CustomDebug, Clone, Copy)]
+#[repr(C)]
+#[debug(unsafe_raw_bytes)]
+pub union Bytes {
+    int: u32,
+    bytes: [u8; 4],
+}
+
+#[derive(CustomDebug, Clone, Copy)]
+#[repr(C, packed)]
+#[debug(union_as = "int")]
+pub union Number {
+    int: u64,
+    float: f64,
+}
+
+fn main() {
+    let header = Header {
+        tag: 1,
+        length: 300,
+        checksum: None,
+    };
+    assert_eq!(
+        format!("{:?}", header),
+        "Header { tag: 1, length: 0x0000012c }"
+    );
+
+    let word = Word { byte: 1 };
+    assert_eq!(format!("{:?}", word), "Word { .. }");
+
+    let bytes = Bytes {
+        bytes: [1, 0, 0, 0],
+    };
+    assert_eq!(format!("{:?}", bytes), "Bytes { bytes: [1, 0, 0, 0] }");
+
+    let number = Number { int: 7 };
+    assert_eq!(format!("{:?}", number), "Number { int: 7 }");
+}
@@ -0,0 +1,12 @@
+// The field named by #[debug(union_as = "...")] must exist in the union.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug, Clone, Copy)]
+#[debug(union_as = "float")]
+pub union Number {
+    int: u64,
+    bits: [u8; 8],
+}
+
+fn main() {}
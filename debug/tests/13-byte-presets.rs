@@ -0,0 +1,57 @@
+// Byte buffers print as long arrays of decimal numbers by default, which is
+// hard to read for protocol structures. Support a few built-in presets on
+// fields that can be viewed as `&[u8]` (arrays, slices, Vec<u8>, ...):
+//
+//   - #[debug(hex)] prints contiguous lowercase hex: `deadbeef`
+//   - #[debug(hexdump)] prints the `hexdump -C` layout
+//   - #[debug(base64)] prints standard padded base64
+//   - #[debug(utf8_lossy)] prints a quoted string, replacing invalid UTF-8
+//
+// The presets format through wrapper types in derive_debug::bytes, which may
+// also be used directly. A generic field with a preset is bounded by
+// `AsRef<[u8]>` rather than Debug.
+
+use derive_debug::bytes::HexDump;
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Packet {
+    #[debug(hex)]
+    magic: [u8; 4],
+    #[debug(base64)]
+    token: Vec<u8>,
+    #[debug(utf8_lossy)]
+    name: &'static [u8],
+    #[debug(hex, skip_if = "<[u8]>::is_empty")]
+    trailer: Box<[u8]>,
+}
+
+#[derive(CustomDebug)]
+pub struct Frame<B> {
+    #[debug(hex)]
+    payload: B,
+}
+
+fn main() {
+    let packet = Packet {
+        magic: [0xde, 0xad, 0xbe, 0xef],
+        token: b"hello".to_vec(),
+        name: b"caf\xc3\xa9 \xff",
+        trailer: Box::new([]),
+    };
+
+    let debug = format!("{:?}", packet);
+    let expected = r#"Packet { magic: deadbeef, token: aGVsbG8=, name: "café �" }"#;
+    assert_eq!(debug, expected);
+
+    let frame = Frame {
+        payload: vec![0x01, 0xff],
+    };
+    assert_eq!(format!("{:?}", frame), "Frame { payload: 01ff }");
+
+    let dump = format!("{:?}", HexDump(b"Hello, world!\n\x00\x01\x02\x03"));
+    let expected = "\
+00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a 00 01  |Hello, world!...|
+00000010  02 03                                             |..|";
+    assert_eq!(dump, expected);
+}
@@ -0,0 +1,13 @@
+// A byte preset decides the whole output of the field, so it cannot be
+// combined with a #[debug = "..."] format string.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Packet {
+    #[debug = "{:?}"]
+    #[debug(hex)]
+    magic: [u8; 4],
+}
+
+fn main() {}
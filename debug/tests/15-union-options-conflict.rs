@@ -0,0 +1,13 @@
+// A union is printed either as one of its fields or as its raw bytes, so
+// #[debug(union_as = "...")] and #[debug(unsafe_raw_bytes)] cannot be combined.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug, Clone, Copy)]
+#[debug(union_as = "int", unsafe_raw_bytes)]
+pub union Number {
+    int: u64,
+    bits: [u8; 8],
+}
+
+fn main() {}
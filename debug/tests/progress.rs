@@ -9,4 +9,11 @@ fn tests() {
     t.pass("tests/06-bound-trouble.rs");
     t.pass("tests/07-associated-type.rs");
     t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-skip-if-non-exhaustive.rs");
+    t.pass("tests/10-debug-fields.rs");
+    t.pass("tests/11-packed-and-union.rs");
+    t.compile_fail("tests/12-union-as-unknown-field.rs");
+    t.pass("tests/13-byte-presets.rs");
+    t.compile_fail("tests/14-byte-preset-conflict.rs");
+    t.compile_fail("tests/15-union-options-conflict.rs");
 }
@@ -5,5 +5,815 @@
 //
 // To run the code:
 //     $ cargo run
+//
+// The binary also puts everything marked #[sorted] in a file into order, the
+// fix the #[sorted] errors suggest:
+//     $ cargo run -- sort path/to/file.rs
+//
+// Enums, structs, impl blocks, traits, use lists and match expressions are
+// reordered in place, each #[sorted::section] on its own. Doc comments,
+// attributes and a comment after the entry on its last line move with it,
+// while comments on lines between entries and section markers stay where they
+// are.
+
+use sorted_order::{is_catch_all, Entry, Sections, SortOrder};
+use std::cmp::Ordering;
+use std::ops::Range;
+use std::{env, fs, process};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{
+    Attribute, Expr, ExprMatch, Fields, Ident, ImplItem, ItemEnum, ItemImpl, ItemStruct, ItemTrait,
+    ItemUse, LitStr, Meta, Pat, TraitItem, UseTree,
+};
+
+/// Nested lists are fixed one level per pass, so give up on anything deeper
+const MAX_PASSES: usize = 16;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.split_first() {
+        None => {}
+        Some((command, paths)) if command == "sort" && !paths.is_empty() => {
+            for path in paths {
+                if let Err(message) = sort_file(path) {
+                    eprintln!("error: {}: {}", path, message);
+                    process::exit(1);
+                }
+            }
+        }
+        Some(_) => {
+            eprintln!("usage: workshop sort <file>...");
+            process::exit(2);
+        }
+    }
+}
+
+fn sort_file(path: &str) -> Result<(), String> {
+    let original = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let sorted = sort_source(&original)?;
+    if sorted != original {
+        fs::write(path, &sorted).map_err(|e| e.to_string())?;
+        println!("sorted {}", path);
+    }
+    Ok(())
+}
+
+/// The source with every list marked #[sorted] put into order
+fn sort_source(original: &str) -> Result<String, String> {
+    // syn skips these before parsing, which would throw off the byte offsets
+    if original.starts_with('\u{feff}')
+        || (original.starts_with("#!") && !original.starts_with("#!["))
+    {
+        return Err("files with a byte order mark or shebang are not supported".to_owned());
+    }
+
+    let mut source = original.to_owned();
+    for _ in 0..MAX_PASSES {
+        let file = syn::parse_file(&source).map_err(|e| e.to_string())?;
+        let mut finder = ListFinder {
+            source: &source,
+            lists: Vec::new(),
+        };
+        finder.visit_file(&file);
+
+        let edits = plan_edits(&source, &finder.lists);
+        if edits.is_empty() {
+            return Ok(source);
+        }
+        for (range, text) in edits.into_iter().rev() {
+            source.replace_range(range, &text);
+        }
+    }
+    Err("lists are nested too deeply".to_owned())
+}
+
+/// The entries of one item, match or section marked #[sorted], with the source
+/// range of everything that moves with each entry
+struct List {
+    order: SortOrder,
+    entries: Vec<(Entry, Range<usize>)>,
+    kind: ListKind,
+    /// Where the last entry ends if it has no comma, which it needs once it
+    /// is moved. Match arms with a block body go without.
+    comma_after: Option<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ListKind {
+    Items,
+    /// Match arms, which are not moved across a catch-all
+    Arms,
+    /// `self` goes first in use lists, whatever the order
+    Use,
+}
+
+impl List {
+    fn new(order: SortOrder, kind: ListKind, comma_after: Option<usize>) -> List {
+        List {
+            order,
+            entries: Vec::new(),
+            kind,
+            comma_after,
+        }
+    }
+
+    fn compare(&self, a: &Entry, b: &Entry) -> Ordering {
+        let is_self = |entry: &Entry| self.kind == ListKind::Use && entry.name == "self";
+        is_self(b)
+            .cmp(&is_self(a))
+            .then_with(|| self.order.compare(a, b))
+    }
+}
+
+/// Replacements that sort every unsorted list, in source order. Lists inside
+/// one being replaced are left for the next pass.
+fn plan_edits(source: &str, lists: &[List]) -> Vec<(Range<usize>, String)> {
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    for list in lists {
+        if list.order == SortOrder::Discriminant || list.entries.len() < 2 {
+            continue;
+        }
+        let mut sorted: Vec<&(Entry, Range<usize>)> = list.entries.iter().collect();
+        sorted.sort_by(|a, b| list.compare(&a.0, &b.0));
+        let in_order = sorted
+            .iter()
+            .zip(&list.entries)
+            .all(|(a, b)| std::ptr::eq(*a, b));
+        if in_order {
+            continue;
+        }
+
+        let ranges: Vec<&Range<usize>> = list.entries.iter().map(|(_, range)| range).collect();
+        let whole = ranges[0].start..ranges[ranges.len() - 1].end;
+        if edits
+            .iter()
+            .any(|(range, _)| range.start < whole.end && whole.start < range.end)
+        {
+            continue;
+        }
+
+        let mut text = String::new();
+        let last = sorted.len() - 1;
+        for (i, (entry, range)) in sorted.iter().enumerate() {
+            let mut chunk = source[range.clone()].to_owned();
+            match list.comma_after {
+                // Before any comment that follows
+                Some(end) if i != last && range.start < end && end <= range.end => {
+                    chunk.insert(end - range.start, ',');
+                }
+                // Without a trailing comma before, the new last entry goes
+                // without one too. Arms keep theirs.
+                Some(_) if i == last && list.kind != ListKind::Arms => {
+                    let tokens_end = entry.span.byte_range().end - range.start;
+                    let rest = &chunk[tokens_end..];
+                    let blanks = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+                    if rest[blanks..].starts_with(',') {
+                        chunk.remove(tokens_end + blanks);
+                    }
+                }
+                _ => {}
+            }
+            text.push_str(&chunk);
+            if let Some(next) = ranges.get(i + 1) {
+                text.push_str(&source[ranges[i].end..next.start]);
+            }
+        }
+        edits.push((whole, text));
+    }
+    edits.sort_by_key(|(range, _)| range.start);
+    edits
+}
+
+struct ListFinder<'a> {
+    source: &'a str,
+    lists: Vec<List>,
+}
+
+impl ListFinder<'_> {
+    /// Add the entries of something marked #[sorted], one list per section as
+    /// the macro splits them. Entries without a name are not moved.
+    fn push(
+        &mut self,
+        attrs: &[Attribute],
+        entries: Vec<(Option<Entry>, &[Attribute])>,
+        kind: ListKind,
+        comma_after: Option<usize>,
+    ) {
+        let Some(order) = sorted_order(attrs) else {
+            return;
+        };
+        let mut sections = match kind {
+            ListKind::Arms => Sections::arms(),
+            _ => Sections::items(),
+        };
+        for (entry, attrs) in entries {
+            let marker = attrs
+                .iter()
+                .rev()
+                .find(|attr| has_path(attr, &["sorted", "section"]));
+            let entry = entry.map(|entry| {
+                let mut range = moving_range(self.source, entry.span);
+                if let Some(marker) = marker {
+                    // The marker stays put, only what follows it moves
+                    let end = marker.span().byte_range().end;
+                    let rest = &self.source[end..];
+                    range.start = end + rest.len() - rest.trim_start().len();
+                }
+                (entry, range)
+            });
+            sections.push(marker.is_some(), entry);
+        }
+        for entries in sections.into_vec() {
+            self.lists.push(List {
+                entries,
+                ..List::new(order, kind, comma_after)
+            });
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for ListFinder<'_> {
+    fn visit_item_enum(&mut self, item: &'ast ItemEnum) {
+        let entries = item
+            .variants
+            .iter()
+            .map(|variant| {
+                let entry = entry(&variant.ident, variant.span());
+                (Some(entry), variant.attrs.as_slice())
+            })
+            .collect();
+        let comma_after = missing_comma(&item.variants);
+        self.push(&item.attrs, entries, ListKind::Items, comma_after);
+        visit::visit_item_enum(self, item);
+    }
+
+    fn visit_item_struct(&mut self, item: &'ast ItemStruct) {
+        if let Fields::Named(fields) = &item.fields {
+            let entries = fields
+                .named
+                .iter()
+                .map(|field| {
+                    let entry = field.ident.as_ref().map(|ident| entry(ident, field.span()));
+                    (entry, field.attrs.as_slice())
+                })
+                .collect();
+            let comma_after = missing_comma(&fields.named);
+            self.push(&item.attrs, entries, ListKind::Items, comma_after);
+        }
+        visit::visit_item_struct(self, item);
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast ItemImpl) {
+        let entries = item
+            .items
+            .iter()
+            .filter_map(|impl_item| {
+                let (attrs, ident) = match impl_item {
+                    ImplItem::Const(item) => (&item.attrs, Some(&item.ident)),
+                    ImplItem::Fn(item) => (&item.attrs, Some(&item.sig.ident)),
+                    ImplItem::Type(item) => (&item.attrs, Some(&item.ident)),
+                    ImplItem::Macro(item) => (&item.attrs, None),
+                    _ => return None,
+                };
+                let entry = ident.map(|ident| entry(ident, impl_item.span()));
+                Some((entry, attrs.as_slice()))
+            })
+            .collect();
+        self.push(&item.attrs, entries, ListKind::Items, None);
+        visit::visit_item_impl(self, item);
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast ItemTrait) {
+        let entries = item
+            .items
+            .iter()
+            .filter_map(|trait_item| {
+                let (attrs, ident) = match trait_item {
+                    TraitItem::Const(item) => (&item.attrs, Some(&item.ident)),
+                    TraitItem::Fn(item) => (&item.attrs, Some(&item.sig.ident)),
+                    TraitItem::Type(item) => (&item.attrs, Some(&item.ident)),
+                    TraitItem::Macro(item) => (&item.attrs, None),
+                    _ => return None,
+                };
+                let entry = ident.map(|ident| entry(ident, trait_item.span()));
+                Some((entry, attrs.as_slice()))
+            })
+            .collect();
+        self.push(&item.attrs, entries, ListKind::Items, None);
+        visit::visit_item_trait(self, item);
+    }
+
+    fn visit_item_use(&mut self, item: &'ast ItemUse) {
+        if let Some(order) = sorted_order(&item.attrs) {
+            use_lists(self.source, &item.tree, order, &mut self.lists);
+        }
+    }
+
+    fn visit_expr_match(&mut self, expr: &'ast ExprMatch) {
+        // Catch-all arms stay last. Matches with other kinds of patterns are
+        // left alone, only arms named by a path are moved.
+        let mut entries = Vec::new();
+        let mut supported = true;
+        for arm in &expr.arms {
+            let path = match &arm.pat {
+                pat if is_catch_all(pat) => {
+                    entries.push((None, arm.attrs.as_slice()));
+                    continue;
+                }
+                Pat::Ident(pat) if pat.subpat.is_none() => {
+                    let entry = entry(&pat.ident, arm.span());
+                    entries.push((Some(entry), arm.attrs.as_slice()));
+                    continue;
+                }
+                Pat::Path(pat) => &pat.path,
+                Pat::TupleStruct(pat) => &pat.path,
+                Pat::Struct(pat) => &pat.path,
+                _ => {
+                    supported = false;
+                    break;
+                }
+            };
+            let name = path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>()
+                .join("::");
+            let entry = Entry {
+                name,
+                span: arm.span(),
+                value: None,
+            };
+            entries.push((Some(entry), arm.attrs.as_slice()));
+        }
+        if supported {
+            let comma_after = expr
+                .arms
+                .last()
+                .filter(|arm| arm.comma.is_none() && !matches!(*arm.body, Expr::Block(_)))
+                .map(|arm| arm.span().byte_range().end);
+            self.push(&expr.attrs, entries, ListKind::Arms, comma_after);
+        }
+        visit::visit_expr_match(self, expr);
+    }
+}
+
+/// Collect every `{...}` list in a use tree
+fn use_lists(source: &str, tree: &UseTree, order: SortOrder, lists: &mut Vec<List>) {
+    match tree {
+        UseTree::Path(path) => use_lists(source, &path.tree, order, lists),
+        UseTree::Group(group) => {
+            let comma_after = missing_comma(&group.items);
+            let mut list = List::new(order, ListKind::Use, comma_after);
+            for tree in &group.items {
+                use_lists(source, tree, order, lists);
+                let name = match tree {
+                    UseTree::Path(path) => path.ident.to_string(),
+                    UseTree::Name(name) => name.ident.to_string(),
+                    UseTree::Rename(rename) => rename.ident.to_string(),
+                    UseTree::Glob(_) => "*".to_owned(),
+                    UseTree::Group(_) => continue,
+                };
+                let span = tree.span();
+                let entry = Entry {
+                    name,
+                    span,
+                    value: None,
+                };
+                list.entries.push((entry, moving_range(source, span)));
+            }
+            lists.push(list);
+        }
+        UseTree::Name(_) | UseTree::Rename(_) | UseTree::Glob(_) => {}
+    }
+}
+
+/// The source range of an entry together with its comma and a comment after it
+/// on the same line, which belongs to the entry and moves with it
+fn moving_range(source: &str, span: proc_macro2::Span) -> Range<usize> {
+    let range = span.byte_range();
+    let skip_blanks = |at: usize| {
+        let rest = &source[at..];
+        at + rest.len() - rest.trim_start_matches([' ', '\t']).len()
+    };
+    let mut end = range.end;
+    let next = skip_blanks(end);
+    if source[next..].starts_with(',') {
+        end = next + 1;
+    }
+    let next = skip_blanks(end);
+    if source[next..].starts_with("//") {
+        end = source[next..].find('\n').map_or(source.len(), |len| next + len);
+    }
+    range.start..end
+}
+
+/// Where the last entry ends, if it has no comma after it
+fn missing_comma<T: Spanned, P>(entries: &Punctuated<T, P>) -> Option<usize> {
+    if entries.trailing_punct() {
+        return None;
+    }
+    entries.last().map(|entry| entry.span().byte_range().end)
+}
+
+fn entry(ident: &Ident, span: proc_macro2::Span) -> Entry {
+    Entry {
+        name: ident.to_string(),
+        span,
+        value: None,
+    }
+}
+
+fn has_path(attr: &Attribute, path: &[&str]) -> bool {
+    let segments: Vec<String> = attr
+        .path()
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    segments == path
+}
+
+/// The order of a `#[sorted]` or `#[sorted::sorted]` attribute, if there is
+/// one whose arguments parse
+fn sorted_order(attrs: &[Attribute]) -> Option<SortOrder> {
+    let attr = attrs
+        .iter()
+        .find(|attr| has_path(attr, &["sorted"]) || has_path(attr, &["sorted", "sorted"]))?;
+    let mut order = SortOrder::default();
+    if let Meta::List(_) = attr.meta {
+        attr.parse_nested_meta(|meta| {
+            // The other arguments don't change the order
+            if meta.path.is_ident("exhaustive_of") {
+                meta.value()?.parse::<LitStr>()?;
+                Ok(())
+            } else if ["names", "from_name", "ord"]
+                .iter()
+                .any(|flag| meta.path.is_ident(flag))
+            {
+                Ok(())
+            } else {
+                order.parse_meta(meta)
+            }
+        })
+        .ok()?;
+    }
+    Some(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sort_source;
+
+    fn assert_sorted(source: &str, expected: &str) {
+        assert_eq!(sort_source(source).unwrap(), expected);
+        // Sorting is idempotent
+        assert_eq!(sort_source(expected).unwrap(), expected);
+    }
+
+    #[test]
+    fn enum_variants_keep_their_docs() {
+        assert_sorted(
+            "
+#[sorted]
+enum Error {
+    /// Timed out
+    Timeout,
+    // Stays here
+    #[allow(dead_code)]
+    Closed,
+    Aborted,
+}
+",
+            "
+#[sorted]
+enum Error {
+    Aborted,
+    // Stays here
+    #[allow(dead_code)]
+    Closed,
+    /// Timed out
+    Timeout,
+}
+",
+        );
+    }
+
+    #[test]
+    fn match_arm_without_trailing_comma() {
+        assert_sorted(
+            "
+fn code(error: Error) -> u8 {
+    #[sorted]
+    match error {
+        Error::Timeout => 2,
+        Error::Closed(_) => 1,
+        Error::Aborted { .. } => 0
+    }
+}
+",
+            "
+fn code(error: Error) -> u8 {
+    #[sorted]
+    match error {
+        Error::Aborted { .. } => 0,
+        Error::Closed(_) => 1,
+        Error::Timeout => 2,
+    }
+}
+",
+        );
+    }
+
+    #[test]
+    fn trailing_comments_move_with_their_entry() {
+        assert_sorted(
+            "
+#[sorted]
+enum Error {
+    Timeout, // after a while
+    Closed,  // by the peer
+    Aborted // by us
+}
+
+fn code(error: Error) -> u8 {
+    #[sorted]
+    match error {
+        Error::Timeout => 2, // slow
+        Error::Aborted => 0 // fast
+    }
+}
+",
+            "
+#[sorted]
+enum Error {
+    Aborted, // by us
+    Closed,  // by the peer
+    Timeout // after a while
+}
+
+fn code(error: Error) -> u8 {
+    #[sorted]
+    match error {
+        Error::Aborted => 0, // fast
+        Error::Timeout => 2, // slow
+    }
+}
+",
+        );
+    }
+
+    #[test]
+    fn match_arm_ending_in_a_struct_expression() {
+        assert_sorted(
+            "
+fn make(e: E) -> S {
+    #[sorted]
+    match e {
+        E::B => S { x: 1 },
+        E::A => S { x: 0 }
+    }
+}
+",
+            "
+fn make(e: E) -> S {
+    #[sorted]
+    match e {
+        E::A => S { x: 0 },
+        E::B => S { x: 1 },
+    }
+}
+",
+        );
+    }
+
+    #[test]
+    fn match_arm_with_a_block_body() {
+        assert_sorted(
+            "
+fn code(e: E) -> u8 {
+    #[sorted]
+    match e {
+        E::B => 1,
+        E::A => {
+            0
+        }
+    }
+}
+",
+            "
+fn code(e: E) -> u8 {
+    #[sorted]
+    match e {
+        E::A => {
+            0
+        }
+        E::B => 1,
+    }
+}
+",
+        );
+    }
+
+    #[test]
+    fn catch_all_stays_last() {
+        assert_sorted(
+            "
+fn code(error: Error) -> u8 {
+    #[sorted]
+    match error {
+        Timeout => 2,
+        Closed => 1,
+        other => 0,
+    }
+}
+",
+            "
+fn code(error: Error) -> u8 {
+    #[sorted]
+    match error {
+        Closed => 1,
+        Timeout => 2,
+        other => 0,
+    }
+}
+",
+        );
+    }
+
+    #[test]
+    fn arms_stay_on_their_side_of_a_guarded_catch_all() {
+        assert_sorted(
+            "
+fn code(error: Error, strict: bool) -> u8 {
+    #[sorted]
+    match error {
+        D => 3,
+        C => 2,
+        _ if strict => 4,
+        B => 1,
+        A => 0,
+        _ => 5,
+    }
+}
+",
+            "
+fn code(error: Error, strict: bool) -> u8 {
+    #[sorted]
+    match error {
+        C => 2,
+        D => 3,
+        _ if strict => 4,
+        A => 0,
+        B => 1,
+        _ => 5,
+    }
+}
+",
+        );
+    }
+
+    #[test]
+    fn sections_are_sorted_separately() {
+        assert_sorted(
+            "
+#[sorted]
+struct Config {
+    name: String,
+    id: u32,
+    #[sorted::section]
+    /// Seconds
+    timeout: u64,
+    retries: u8,
+}
+",
+            "
+#[sorted]
+struct Config {
+    id: u32,
+    name: String,
+    #[sorted::section]
+    retries: u8,
+    /// Seconds
+    timeout: u64,
+}
+",
+        );
+    }
+
+    #[test]
+    fn nested_use_groups() {
+        assert_sorted(
+            "
+#[sorted]
+use std::{io::{Write, Read}, fmt, self};
+",
+            "
+#[sorted]
+use std::{self, fmt, io::{Read, Write}};
+",
+        );
+    }
+
+    #[test]
+    fn orders_from_the_attribute() {
+        assert_sorted(
+            "
+#[sorted(order = \"natural\")]
+enum Version {
+    Http10,
+    Http2,
+    Http11,
+}
+",
+            "
+#[sorted(order = \"natural\")]
+enum Version {
+    Http2,
+    Http10,
+    Http11,
+}
+",
+        );
+    }
+
+    #[test]
+    fn enum_helper_flags_are_ignored() {
+        assert_sorted(
+            "
+#[sorted(ord)]
+enum Kind {
+    B,
+    A,
+}
+
+#[sorted(names, from_name)]
+enum Level {
+    Warn,
+    Info,
+}
+",
+            "
+#[sorted(ord)]
+enum Kind {
+    A,
+    B,
+}
+
+#[sorted(names, from_name)]
+enum Level {
+    Info,
+    Warn,
+}
+",
+        );
+    }
+
+    #[test]
+    fn exhaustive_of_is_ignored() {
+        assert_sorted(
+            "
+fn code(kind: Kind) -> u8 {
+    #[sorted(exhaustive_of = \"Kind\", order = \"reverse\")]
+    match kind {
+        Kind::A => 0,
+        Kind::B => 1,
+    }
+}
+",
+            "
+fn code(kind: Kind) -> u8 {
+    #[sorted(exhaustive_of = \"Kind\", order = \"reverse\")]
+    match kind {
+        Kind::B => 1,
+        Kind::A => 0,
+    }
+}
+",
+        );
+    }
+
+    #[test]
+    fn unmarked_and_sorted_code_is_untouched() {
+        let source = "
+enum Unmarked {
+    B,
+    A,
+}
+
+#[sorted]
+enum Marked {
+    A,
+    B,
+}
+";
+        assert_sorted(source, source);
+    }
 
-fn main() {}
+    #[test]
+    fn shebang_is_rejected() {
+        assert!(sort_source("#!/usr/bin/env run-cargo-script\nfn main() {}\n").is_err());
+    }
+}
@@ -0,0 +1,135 @@
+// Measures how long seq! takes to expand large bodies, outside the compiler.
+//
+//     cargo bench -p seq
+//
+// Each case prints the fastest and the mean time of a number of runs.
+
+use proc_macro2::TokenStream;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const RUNS: u32 = 20;
+
+fn main() {
+    bench("opcode table, 256 match arms", opcode_table());
+    bench("256 impls with large constant bodies", impls());
+    bench("16 x 16 nested loops", nested());
+    bench("attribute on an enum with 256 variants", enum_variants());
+}
+
+/// A 256-arm dispatch like an emulator's, one `#(...)*` section
+fn opcode_table() -> TokenStream {
+    tokens(
+        r#"
+        N in 0..256 {
+            fn execute(cpu: &mut Cpu, op: u8) {
+                match op {
+                    #(
+                        N => {
+                            let (lhs, rhs) = (cpu.reg[(N & 0x7) as usize], cpu.reg[(N >> 3) as usize]);
+                            cpu.flags.set(Flags::ZERO, lhs == rhs);
+                            cpu.cycles += op_~N::CYCLES;
+                            op_~N::run(cpu, lhs, rhs);
+                        }
+                    )*
+                }
+            }
+        }
+        "#,
+    )
+}
+
+/// The whole body repeated, with most of it not mentioning N
+fn impls() -> TokenStream {
+    tokens(
+        r#"
+        N in 0..256 {
+            impl Handler for Op~N {
+                const CODE: u8 = N;
+
+                fn run(&self, cpu: &mut Cpu) {
+                    let mut acc = cpu.acc;
+                    for i in 0..cpu.reg.len() {
+                        acc = acc.wrapping_mul(31).wrapping_add(cpu.reg[i] as u32);
+                        if acc & 1 == 0 {
+                            cpu.flags.insert(Flags::EVEN);
+                        } else {
+                            cpu.flags.remove(Flags::EVEN);
+                        }
+                    }
+                    cpu.trace.push(Trace { pc: cpu.pc, acc, flags: cpu.flags.bits() });
+                    cpu.acc = acc;
+                }
+            }
+        }
+        "#,
+    )
+}
+
+/// An outer seq! whose body holds an inner one
+fn nested() -> TokenStream {
+    tokens(
+        r#"
+        R in 0..16 {
+            seq!(C in 0..16 {
+                const ROW_~R: [u32; 16] = [#(R * 16 + C,)*];
+            });
+        }
+        "#,
+    )
+}
+
+fn enum_variants() -> TokenStream {
+    tokens(
+        r#"
+        #[derive(Debug)]
+        enum Opcode {
+            Nop,
+            #[repeat]
+            Op_N = N,
+        }
+        "#,
+    )
+}
+
+fn tokens(source: &str) -> TokenStream {
+    source.parse().expect("benchmark input is valid tokens")
+}
+
+fn bench(name: &str, input: TokenStream) {
+    let expand = |input: TokenStream| {
+        if name.starts_with("attribute") {
+            seq_expand::repeat(tokens("N in 0..256"), input)
+        } else {
+            seq_expand::seq(input)
+        }
+    };
+
+    let mut times = Vec::new();
+    let mut tokens = 0;
+    for _ in 0..RUNS {
+        let input = input.clone();
+        let start = Instant::now();
+        let output = black_box(expand(input).expect("benchmark input expands"));
+        times.push(start.elapsed());
+        tokens = count_tokens(&output);
+    }
+
+    let fastest = times.iter().min().unwrap();
+    let mean = times.iter().sum::<Duration>() / RUNS;
+    println!(
+        "{:<40} {:>10.2?} fastest {:>10.2?} mean  ({} tokens)",
+        name, fastest, mean, tokens
+    );
+}
+
+fn count_tokens(tokens: &TokenStream) -> usize {
+    tokens
+        .clone()
+        .into_iter()
+        .map(|tt| match tt {
+            proc_macro2::TokenTree::Group(group) => 1 + count_tokens(&group.stream()),
+            _ => 1,
+        })
+        .sum()
+}
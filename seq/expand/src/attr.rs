@@ -0,0 +1,217 @@
+// #[seq::repeat(N in 0..8)] repeats the item it is attached to, as if the item
+// were the body of seq!, so that it stays visible to rustdoc and IDEs.
+//
+// The compiler parses the item before the attribute sees it, so `Reg~N` cannot
+// be written there. Instead a loop variable is pasted where it appears as a
+// word of an identifier between underscores: `R_N` becomes `R_0`, and
+// `field_N_mask` becomes `field_0_mask`.
+//
+// If any variant or field of the item is marked with a bare #[repeat], only
+// the marked entries are repeated and the rest of the item is emitted once:
+//
+//     #[seq::repeat(N in 0..4)]
+//     enum Reg {
+//         #[repeat]
+//         R_N,
+//         Pc,
+//     }
+
+use crate::expand::{expand_body, iterations, parse_loops, split_commas};
+use crate::expand::{substitute, template_for, Position, SeqLoop};
+use proc_macro2::{
+    Delimiter, Group, Ident, Punct, Spacing, TokenStream as TokenStream2, TokenTree,
+};
+use syn::parse::{ParseStream, Parser};
+use syn::Result;
+
+/// Expand `#[seq::repeat(N in 0..8)]` on an item
+pub(crate) fn repeat(args: TokenStream2, item: TokenStream2) -> Result<TokenStream2> {
+    let loops = parse_args.parse2(args)?;
+    expand_item(&loops, item)
+}
+
+/// Parse the attribute arguments, a seq! header without the body
+fn parse_args(input: ParseStream) -> Result<Vec<SeqLoop>> {
+    let loops = parse_loops(input)?;
+    if !input.is_empty() {
+        return Err(input.error("unexpected token after seq header"));
+    }
+    Ok(loops)
+}
+
+fn expand_item(loops: &[SeqLoop], item: TokenStream2) -> Result<TokenStream2> {
+    let iterations = iterations(loops);
+
+    // Only what is repeated gets its pastes marked, the rest is emitted as is
+    let mut tokens: Vec<TokenTree> = item.clone().into_iter().collect();
+    let Some(body_index) = find_body(&tokens) else {
+        return expand_body(loops, &mark_pastes(item, loops), &iterations);
+    };
+    let TokenTree::Group(body) = &tokens[body_index] else {
+        unreachable!("find_body returns the index of a group");
+    };
+
+    let entries: Vec<(TokenStream2, bool)> = split_commas(body.stream())
+        .into_iter()
+        .map(take_marker)
+        .collect();
+    if !entries.iter().any(|(_, marked)| *marked) {
+        return expand_body(loops, &mark_pastes(item, loops), &iterations);
+    }
+
+    // Like #(...) sections, marked entries may be repeated without using the
+    // loop variable, e.g. `#[repeat] u32` in a tuple struct
+    let mut stream = TokenStream2::new();
+    for (entry, marked) in &entries {
+        if !marked {
+            stream.extend(entry.clone());
+            stream.extend([comma()]);
+            continue;
+        }
+        let template = template_for(&mark_pastes(entry.clone(), loops), &iterations);
+        for (index, bindings) in iterations.iter().enumerate() {
+            let position = Position {
+                index,
+                count: iterations.len(),
+            };
+            stream.extend(substitute(&template, bindings, Some(position))?);
+            stream.extend([comma()]);
+        }
+    }
+
+    let mut new_body = Group::new(body.delimiter(), stream);
+    new_body.set_span(body.span());
+    tokens[body_index] = TokenTree::Group(new_body);
+    Ok(tokens.into_iter().collect())
+}
+
+/// Rewrite identifiers with a loop variable as one of their underscore
+/// separated words into the `~` pasting of seq! bodies, `R_N` into `R_ ~ N`
+fn mark_pastes(tokens: TokenStream2, loops: &[SeqLoop]) -> TokenStream2 {
+    let is_var = |word: &str| loops.iter().any(|seq_loop| seq_loop.var == word);
+    let mut output = TokenStream2::new();
+    for tt in tokens {
+        match tt {
+            TokenTree::Ident(ident) => {
+                let name = ident.to_string();
+                let words: Vec<&str> = name.split('_').collect();
+                if words.len() < 2 || !words.iter().any(|word| is_var(word)) {
+                    output.extend([TokenTree::Ident(ident)]);
+                    continue;
+                }
+
+                // Each variable becomes its own segment and the underscores
+                // around it stay with the neighbouring text
+                let mut segments = vec![String::new()];
+                for (i, word) in words.iter().enumerate() {
+                    if i > 0 {
+                        segments.last_mut().unwrap().push('_');
+                    }
+                    if is_var(word) {
+                        segments.push(word.to_string());
+                        segments.push(String::new());
+                    } else {
+                        segments.last_mut().unwrap().push_str(word);
+                    }
+                }
+                segments.retain(|segment| !segment.is_empty());
+
+                for (i, segment) in segments.iter().enumerate() {
+                    if i > 0 {
+                        let mut tilde = Punct::new('~', Spacing::Alone);
+                        tilde.set_span(ident.span());
+                        output.extend([TokenTree::Punct(tilde)]);
+                    }
+                    output.extend([TokenTree::Ident(Ident::new(segment, ident.span()))]);
+                }
+            }
+            TokenTree::Group(group) => {
+                let mut new_group =
+                    Group::new(group.delimiter(), mark_pastes(group.stream(), loops));
+                new_group.set_span(group.span());
+                output.extend([TokenTree::Group(new_group)]);
+            }
+            tt => output.extend([tt]),
+        }
+    }
+    output
+}
+
+/// The variants or fields of a struct or enum: the last braced group at the
+/// top level, or the parenthesized fields of a tuple struct
+fn find_body(tokens: &[TokenTree]) -> Option<usize> {
+    let is_kind = |kind: &str| {
+        tokens
+            .iter()
+            .any(|tt| matches!(tt, TokenTree::Ident(ident) if ident == kind))
+    };
+    if !is_kind("struct") && !is_kind("enum") && !is_kind("union") {
+        return None;
+    }
+    let is_group = |tt: &TokenTree, delimiter| matches!(tt, TokenTree::Group(group) if group.delimiter() == delimiter);
+    tokens
+        .iter()
+        .rposition(|tt| is_group(tt, Delimiter::Brace))
+        .or_else(|| tuple_fields(tokens))
+}
+
+/// The fields of a tuple struct, which follow the name and any generics. Other
+/// parentheses can come earlier, as in `pub(crate)` or `#[derive(...)]`, or in
+/// the generics, as in `<F: Fn(u8)>`.
+fn tuple_fields(tokens: &[TokenTree]) -> Option<usize> {
+    let keyword = tokens
+        .iter()
+        .position(|tt| matches!(tt, TokenTree::Ident(ident) if ident == "struct"))?;
+    // Skip the keyword and the name
+    let mut i = keyword + 2;
+    let mut depth = 0usize;
+    while let Some(tt) = tokens.get(i) {
+        match tt {
+            TokenTree::Punct(p) if p.as_char() == '<' => depth += 1,
+            // The `>` of a `->` does not close the generics
+            TokenTree::Punct(p) if p.as_char() == '>' && !is_arrow(tokens, i) => {
+                depth = depth.checked_sub(1)?;
+            }
+            TokenTree::Group(group)
+                if depth == 0 && group.delimiter() == Delimiter::Parenthesis =>
+            {
+                return Some(i);
+            }
+            _ if depth == 0 => return None,
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+fn is_arrow(tokens: &[TokenTree], i: usize) -> bool {
+    matches!(
+        i.checked_sub(1).and_then(|prev| tokens.get(prev)),
+        Some(TokenTree::Punct(p)) if p.as_char() == '-' && p.spacing() == Spacing::Joint
+    )
+}
+
+/// Remove a `#[repeat]` marker from the attributes of an entry, returning
+/// whether there was one
+fn take_marker(entry: TokenStream2) -> (TokenStream2, bool) {
+    let mut tokens: Vec<TokenTree> = entry.into_iter().collect();
+    let mut i = 0;
+    while let (Some(TokenTree::Punct(pound)), Some(TokenTree::Group(attr))) =
+        (tokens.get(i), tokens.get(i + 1))
+    {
+        if pound.as_char() != '#' || attr.delimiter() != Delimiter::Bracket {
+            break;
+        }
+        if attr.stream().to_string() == "repeat" {
+            tokens.drain(i..i + 2);
+            return (tokens.into_iter().collect(), true);
+        }
+        i += 2;
+    }
+    (tokens.into_iter().collect(), false)
+}
+
+fn comma() -> TokenTree {
+    TokenTree::Punct(Punct::new(',', Spacing::Alone))
+}
@@ -0,0 +1,274 @@
+// Expansion-time evaluation of integer expressions in seq! range bounds.
+//
+// Proc macros run before consts are evaluated, so a bound can only be built
+// from literals. This supports the usual integer operators between them so
+// that `0..4 * 8` or `1..=(1 << 4)` work, including when the expression comes
+// from a macro_rules! `$bits:expr` (an invisible None-delimited group).
+//
+// The same evaluator decides `#if(...)` conditions in the body. Comparisons
+// and `&&`, `||`, `!` work on integers as in C: false is 0, true is 1 and any
+// nonzero value counts as true.
+
+use crate::format::LitFormat;
+use proc_macro2::{Delimiter, TokenTree};
+use syn::parse::{ParseStream, Parser};
+use syn::{Error, LitBool, LitChar, LitInt, Result, Token};
+
+/// The value of an expression and the format of the literals it was built from
+pub(crate) struct Evaluated {
+    pub value: i128,
+    pub format: LitFormat,
+}
+
+#[derive(Clone, Copy)]
+enum BinOp {
+    Mul,
+    Div,
+    Rem,
+    Add,
+    Sub,
+    Shl,
+    Shr,
+    BitAnd,
+    BitXor,
+    BitOr,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+impl BinOp {
+    /// Binding strength, following Rust's operator precedence
+    fn precedence(self) -> u8 {
+        match self {
+            BinOp::Mul | BinOp::Div | BinOp::Rem => 9,
+            BinOp::Add | BinOp::Sub => 8,
+            BinOp::Shl | BinOp::Shr => 7,
+            BinOp::BitAnd => 6,
+            BinOp::BitXor => 5,
+            BinOp::BitOr => 4,
+            BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => 3,
+            BinOp::And => 2,
+            BinOp::Or => 1,
+        }
+    }
+
+    fn apply(self, lhs: i128, rhs: i128) -> Option<i128> {
+        match self {
+            BinOp::Mul => lhs.checked_mul(rhs),
+            BinOp::Div => lhs.checked_div(rhs),
+            BinOp::Rem => lhs.checked_rem(rhs),
+            BinOp::Add => lhs.checked_add(rhs),
+            BinOp::Sub => lhs.checked_sub(rhs),
+            BinOp::Shl => lhs.checked_shl(u32::try_from(rhs).ok()?),
+            BinOp::Shr => lhs.checked_shr(u32::try_from(rhs).ok()?),
+            BinOp::BitAnd => Some(lhs & rhs),
+            BinOp::BitXor => Some(lhs ^ rhs),
+            BinOp::BitOr => Some(lhs | rhs),
+            BinOp::Eq => Some((lhs == rhs).into()),
+            BinOp::Ne => Some((lhs != rhs).into()),
+            BinOp::Lt => Some((lhs < rhs).into()),
+            BinOp::Le => Some((lhs <= rhs).into()),
+            BinOp::Gt => Some((lhs > rhs).into()),
+            BinOp::Ge => Some((lhs >= rhs).into()),
+            BinOp::And => Some((lhs != 0 && rhs != 0).into()),
+            BinOp::Or => Some((lhs != 0 || rhs != 0).into()),
+        }
+    }
+}
+
+/// Parse and evaluate an integer expression
+pub(crate) fn parse_expr(input: ParseStream) -> Result<Evaluated> {
+    parse_binary(input, 0)
+}
+
+/// Parse and evaluate the whole input as a condition
+pub(crate) fn parse_condition(input: ParseStream) -> Result<bool> {
+    Ok(parse_complete(input)?.value != 0)
+}
+
+fn parse_binary(input: ParseStream, min_precedence: u8) -> Result<Evaluated> {
+    let mut lhs = parse_unary(input)?;
+
+    while let Some(op) = peek_binop(input) {
+        if op.precedence() < min_precedence {
+            break;
+        }
+        let op_span = input.span();
+        parse_binop(input, op)?;
+
+        let rhs = parse_binary(input, op.precedence() + 1)?;
+        lhs = Evaluated {
+            value: op
+                .apply(lhs.value, rhs.value)
+                .ok_or_else(|| Error::new(op_span, "overflow or division by zero in seq! bound"))?,
+            format: lhs.format.merge(rhs.format, op_span)?,
+        };
+    }
+
+    Ok(lhs)
+}
+
+fn peek_binop(input: ParseStream) -> Option<BinOp> {
+    // Multi-character operators first so `<<` is not taken for something else
+    if input.peek(Token![<<]) {
+        Some(BinOp::Shl)
+    } else if input.peek(Token![>>]) {
+        Some(BinOp::Shr)
+    } else if input.peek(Token![&&]) {
+        Some(BinOp::And)
+    } else if input.peek(Token![||]) {
+        Some(BinOp::Or)
+    } else if input.peek(Token![==]) {
+        Some(BinOp::Eq)
+    } else if input.peek(Token![!=]) {
+        Some(BinOp::Ne)
+    } else if input.peek(Token![<=]) {
+        Some(BinOp::Le)
+    } else if input.peek(Token![>=]) {
+        Some(BinOp::Ge)
+    } else if input.peek(Token![<]) {
+        Some(BinOp::Lt)
+    } else if input.peek(Token![>]) {
+        Some(BinOp::Gt)
+    } else if input.peek(Token![*]) {
+        Some(BinOp::Mul)
+    } else if input.peek(Token![/]) {
+        Some(BinOp::Div)
+    } else if input.peek(Token![%]) {
+        Some(BinOp::Rem)
+    } else if input.peek(Token![+]) {
+        Some(BinOp::Add)
+    } else if input.peek(Token![-]) {
+        Some(BinOp::Sub)
+    } else if input.peek(Token![&]) {
+        Some(BinOp::BitAnd)
+    } else if input.peek(Token![^]) {
+        Some(BinOp::BitXor)
+    } else if input.peek(Token![|]) {
+        Some(BinOp::BitOr)
+    } else {
+        None
+    }
+}
+
+fn parse_binop(input: ParseStream, op: BinOp) -> Result<()> {
+    match op {
+        BinOp::Mul => input.parse::<Token![*]>().map(drop),
+        BinOp::Div => input.parse::<Token![/]>().map(drop),
+        BinOp::Rem => input.parse::<Token![%]>().map(drop),
+        BinOp::Add => input.parse::<Token![+]>().map(drop),
+        BinOp::Sub => input.parse::<Token![-]>().map(drop),
+        BinOp::Shl => input.parse::<Token![<<]>().map(drop),
+        BinOp::Shr => input.parse::<Token![>>]>().map(drop),
+        BinOp::BitAnd => input.parse::<Token![&]>().map(drop),
+        BinOp::BitXor => input.parse::<Token![^]>().map(drop),
+        BinOp::BitOr => input.parse::<Token![|]>().map(drop),
+        BinOp::Eq => input.parse::<Token![==]>().map(drop),
+        BinOp::Ne => input.parse::<Token![!=]>().map(drop),
+        BinOp::Lt => input.parse::<Token![<]>().map(drop),
+        BinOp::Le => input.parse::<Token![<=]>().map(drop),
+        BinOp::Gt => input.parse::<Token![>]>().map(drop),
+        BinOp::Ge => input.parse::<Token![>=]>().map(drop),
+        BinOp::And => input.parse::<Token![&&]>().map(drop),
+        BinOp::Or => input.parse::<Token![||]>().map(drop),
+    }
+}
+
+fn parse_unary(input: ParseStream) -> Result<Evaluated> {
+    if input.peek(Token![-]) {
+        let span = input.span();
+        input.parse::<Token![-]>()?;
+        let operand = parse_unary(input)?;
+        return Ok(Evaluated {
+            value: operand
+                .value
+                .checked_neg()
+                .ok_or_else(|| Error::new(span, "overflow in seq! bound"))?,
+            format: operand.format,
+        });
+    }
+    if input.peek(Token![!]) {
+        input.parse::<Token![!]>()?;
+        let operand = parse_unary(input)?;
+        return Ok(Evaluated {
+            value: (operand.value == 0).into(),
+            format: operand.format,
+        });
+    }
+    parse_primary(input)
+}
+
+fn parse_primary(input: ParseStream) -> Result<Evaluated> {
+    // A macro_rules! `$e:expr` arrives as an invisible group and must keep
+    // its own precedence, like parentheses
+    let invisible = input.step(|cursor| match cursor.token_tree() {
+        Some((TokenTree::Group(group), rest)) if group.delimiter() == Delimiter::None => {
+            Ok((Some(group), rest))
+        }
+        _ => Ok((None, *cursor)),
+    })?;
+    if let Some(group) = invisible {
+        return parse_complete.parse2(group.stream());
+    }
+
+    if input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in input);
+        return parse_complete(&content);
+    }
+
+    if input.peek(LitInt) {
+        let lit: LitInt = input.parse()?;
+        return Ok(Evaluated {
+            value: lit.base10_parse()?,
+            format: LitFormat::of_literal(&lit),
+        });
+    }
+
+    if input.peek(LitBool) {
+        let lit: LitBool = input.parse()?;
+        return Ok(Evaluated {
+            value: lit.value.into(),
+            format: LitFormat::default(),
+        });
+    }
+
+    // Characters compare by code point, for `#if(L != 'a')`
+    if input.peek(LitChar) {
+        let lit: LitChar = input.parse()?;
+        return Ok(Evaluated {
+            value: u32::from(lit.value()).into(),
+            format: LitFormat::default(),
+        });
+    }
+
+    if input.peek(syn::Ident) {
+        let ident: syn::Ident = input.parse()?;
+        return Err(Error::new(
+            ident.span(),
+            format!(
+                "seq! cannot read the value of `{}` because macros expand before \
+                 consts are evaluated; pass the value in from a \
+                 macro_rules! callback instead",
+                ident
+            ),
+        ));
+    }
+
+    Err(input.error("expected integer literal"))
+}
+
+/// Parse an expression that must use up the whole input
+fn parse_complete(input: ParseStream) -> Result<Evaluated> {
+    let value = parse_expr(input)?;
+    if !input.is_empty() {
+        return Err(input.error("unexpected token in seq! bound"));
+    }
+    Ok(value)
+}
@@ -0,0 +1,1154 @@
+// Parsing of the seq! header and expansion of the body.
+
+use proc_macro2::{
+    Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream as TokenStream2, TokenTree,
+};
+use syn::{buffer::Cursor, parse::Parse, Token};
+
+use crate::eval;
+use crate::format::{FormatSpec, LitFormat};
+use crate::template::{Template, TokenIter};
+
+/// Iterations allowed unless raised with `limit = N` in the header, so that a
+/// typo like `0..1_000_000` does not silently produce millions of copies
+const DEFAULT_LIMIT: u128 = 65_536;
+
+mod kw {
+    syn::custom_keyword!(limit);
+    syn::custom_keyword!(rev);
+    syn::custom_keyword!(step);
+    syn::custom_keyword!(step_by);
+}
+
+struct SeqInput {
+    /// `R in 0..4, C in 0..4` iterates over the cartesian product, last
+    /// variable fastest
+    loops: Vec<SeqLoop>,
+    body: TokenStream2,
+}
+
+pub(crate) struct SeqLoop {
+    pub var: Ident,
+    source: Source,
+    /// The range or list as written, for errors about it
+    tokens: TokenStream2,
+}
+
+/// What a loop variable iterates over
+enum Source {
+    /// `0..8`, `'a'..='f'`, with any adapters
+    Range(SeqRange),
+    /// `[u8, u16, Vec<u32>]`, one element of tokens per iteration
+    List(Vec<TokenStream2>),
+}
+
+/// A loop variable in scope while substituting
+#[derive(Clone, Copy)]
+pub(crate) struct Binding<'a> {
+    var: &'a Ident,
+    /// `None` for a variable declared by a nested seq!, which shadows outer
+    /// variables and is left for the inner macro to substitute
+    value: Option<Value<'a>>,
+}
+
+/// The value of a loop variable in the current iteration
+#[derive(Clone, Copy)]
+enum Value<'a> {
+    /// An integer and the format of the range's literals
+    Int(i128, &'a LitFormat),
+    Char(char),
+    /// An element of a list, substituted as is
+    Tokens(&'a TokenStream2),
+}
+
+/// The bindings of every iteration, in order
+pub(crate) fn iterations(loops: &[SeqLoop]) -> Vec<Vec<Binding<'_>>> {
+    let mut iterations = vec![Vec::new()];
+    for seq_loop in loops {
+        let values = seq_loop.values();
+        iterations = iterations
+            .into_iter()
+            .flat_map(|outer| {
+                values.iter().map(move |&value| {
+                    let mut bindings = outer.clone();
+                    bindings.push(Binding {
+                        var: &seq_loop.var,
+                        value: Some(value),
+                    });
+                    bindings
+                })
+            })
+            .collect();
+    }
+    iterations
+}
+
+impl SeqLoop {
+    /// The number of iterations, without building the values
+    fn len(&self) -> u128 {
+        match &self.source {
+            Source::Range(range) => range.len(),
+            Source::List(elements) => elements.len() as u128,
+        }
+    }
+
+    /// The values the loop variable takes, in iteration order
+    fn values(&self) -> Vec<Value<'_>> {
+        match &self.source {
+            Source::Range(range) if range.chars => range
+                .values()
+                .filter_map(|code| char::from_u32(u32::try_from(code).ok()?))
+                .map(Value::Char)
+                .collect(),
+            Source::Range(range) => range
+                .values()
+                .map(|value| Value::Int(value, &range.format))
+                .collect(),
+            Source::List(elements) => elements.iter().map(Value::Tokens).collect(),
+        }
+    }
+}
+
+/// An integer or character range plus the `step`/`rev` adapters, applied in
+/// order. Characters are iterated by code point, skipping surrogates.
+struct SeqRange {
+    start: i128,
+    end: i128,
+    inclusive: bool,
+    chars: bool,
+    adapters: Vec<Adapter>,
+    /// Suffix and radix of the bound literals, reused for substituted values
+    format: LitFormat,
+}
+
+enum Adapter {
+    StepBy(usize),
+    Rev,
+}
+
+impl SeqRange {
+    /// The range after its adapters as `(first, stride, len)`, so that a large
+    /// stepped range costs no more than the iterations it produces
+    fn progression(&self) -> (i128, i128, u128) {
+        let end = self.end.saturating_add(i128::from(self.inclusive));
+        let mut len = end.saturating_sub(self.start).max(0).unsigned_abs();
+        let (mut first, mut stride) = (self.start, 1i128);
+        for adapter in &self.adapters {
+            match adapter {
+                Adapter::StepBy(step) => {
+                    stride = stride.saturating_mul(*step as i128);
+                    len = len.div_ceil(*step as u128);
+                }
+                Adapter::Rev => {
+                    if len > 0 {
+                        first += stride * (len - 1) as i128;
+                    }
+                    stride = stride.saturating_neg();
+                }
+            }
+        }
+        (first, stride, len)
+    }
+
+    /// The number of integers or code points in the range
+    fn len(&self) -> u128 {
+        self.progression().2
+    }
+
+    /// Reject a range whose end comes before its start, which would silently
+    /// expand to nothing
+    fn check_order(&self, tokens: TokenStream2) -> syn::Result<()> {
+        if self.end >= self.start {
+            return Ok(());
+        }
+        let show = |value: i128| match self.chars {
+            true => format!("{:?}", char::from_u32(value as u32).unwrap_or_default()),
+            false => value.to_string(),
+        };
+        Err(syn::Error::new_spanned(
+            tokens,
+            format!(
+                "seq! range ends at {} before it starts at {}; put `rev` before an \
+                 increasing range to count down",
+                show(self.end),
+                show(self.start)
+            ),
+        ))
+    }
+
+    /// The integers or code points of the range, in iteration order
+    fn values(&self) -> impl Iterator<Item = i128> {
+        let (first, stride, len) = self.progression();
+        (0..len).map(move |i| first + stride * i as i128)
+    }
+}
+
+impl Parse for SeqInput {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let loops = parse_loops(input)?;
+
+        // Parse the body as a braced group
+        let content;
+        syn::braced!(content in input);
+        let body: TokenStream2 = content.parse()?;
+
+        Ok(SeqInput { loops, body })
+    }
+}
+
+/// Parse the loops of a header such as `R in 0..4, C in 0..4, limit = 100`
+pub(crate) fn parse_loops(input: syn::parse::ParseStream) -> syn::Result<Vec<SeqLoop>> {
+    let mut loops = Vec::new();
+    loop {
+        let var: Ident = input.parse()?;
+        input.parse::<Token![in]>()?;
+        let begin = input.cursor();
+        let source = if input.peek(syn::token::Bracket) {
+            Source::List(parse_list(input)?)
+        } else {
+            Source::Range(parse_seq_range(input)?)
+        };
+        let tokens = tokens_between(begin, input.cursor());
+        loops.push(SeqLoop {
+            var,
+            source,
+            tokens,
+        });
+
+        if input.parse::<Option<Token![,]>>()?.is_none() {
+            break;
+        }
+        // `limit = N` raises the iteration cap and ends the header
+        if input.peek(kw::limit) && input.peek2(Token![=]) {
+            break;
+        }
+    }
+
+    let mut limit = DEFAULT_LIMIT;
+    if input.peek(kw::limit) {
+        input.parse::<kw::limit>()?;
+        input.parse::<Token![=]>()?;
+        limit = input.parse::<syn::LitInt>()?.base10_parse()?;
+    }
+    check_limit(&loops, limit)?;
+    Ok(loops)
+}
+
+/// The tokens parsed between two cursors, for errors spanning all of them
+fn tokens_between(begin: Cursor, end: Cursor) -> TokenStream2 {
+    let mut tokens = TokenStream2::new();
+    let mut cursor = begin;
+    while cursor != end {
+        let Some((tt, next)) = cursor.token_tree() else {
+            break;
+        };
+        tokens.extend([tt]);
+        cursor = next;
+    }
+    tokens
+}
+
+/// Reject invocations that would expand to more than `limit` iterations. This
+/// runs before any values are generated, so a rejected range costs nothing.
+fn check_limit(loops: &[SeqLoop], limit: u128) -> syn::Result<()> {
+    let mut total: u128 = 1;
+    for seq_loop in loops {
+        total = total.saturating_mul(seq_loop.len());
+        if total > limit {
+            return Err(syn::Error::new_spanned(
+                &seq_loop.tokens,
+                format!(
+                    "seq! would expand to {} iterations, more than the limit of {}; \
+                     add `limit = {}` to the header if this is intended",
+                    total, limit, total
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// When the whole body is repeated, every loop variable must appear in it
+/// unless its name starts with an underscore. Repeat sections are exempt as
+/// `#(...)*` is also the way to repeat something a number of times.
+fn check_vars_used(loops: &[SeqLoop], body: &TokenStream2, template: &Template) -> syn::Result<()> {
+    if body.is_empty() || template.has_repeat() {
+        return Ok(());
+    }
+    for seq_loop in loops {
+        let var = &seq_loop.var;
+        if !var.to_string().starts_with('_') && !mentions(body, var) {
+            return Err(syn::Error::new(
+                var.span(),
+                format!(
+                    "loop variable `{}` is never used in the body; \
+                     rename it to `_{}` if this is intended",
+                    var, var
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `var` appears anywhere in the tokens, including as `~var` in a
+/// string literal
+fn mentions(tokens: &TokenStream2, var: &Ident) -> bool {
+    let pasted = format!("~{}", var);
+    tokens.clone().into_iter().any(|tt| match tt {
+        TokenTree::Ident(ident) => ident == *var,
+        TokenTree::Literal(lit) => lit.to_string().contains(&pasted),
+        TokenTree::Group(g) => mentions(&g.stream(), var),
+        TokenTree::Punct(_) => false,
+    })
+}
+
+/// Parse a range together with its `rev`, `step` or `.step_by()` adapters
+fn parse_seq_range(input: syn::parse::ParseStream) -> syn::Result<SeqRange> {
+    // `rev 0..8` iterates backwards, after any `step` has been applied
+    let rev = input.parse::<Option<kw::rev>>()?.is_some();
+
+    let mut range = if input.peek(syn::token::Paren) && is_parenthesized_range(input) {
+        // `(0..64).step_by(8).rev()` applies the adapters in call order
+        let content;
+        syn::parenthesized!(content in input);
+        let mut range = parse_range(&content)?;
+        while input.peek(Token![.]) {
+            input.parse::<Token![.]>()?;
+            if input.peek(kw::step_by) {
+                input.parse::<kw::step_by>()?;
+                let arg;
+                syn::parenthesized!(arg in input);
+                range.adapters.push(Adapter::StepBy(parse_step(&arg)?));
+            } else if input.peek(kw::rev) {
+                input.parse::<kw::rev>()?;
+                let arg;
+                syn::parenthesized!(arg in input);
+                if !arg.is_empty() {
+                    return Err(arg.error("rev() takes no arguments"));
+                }
+                range.adapters.push(Adapter::Rev);
+            } else {
+                return Err(input.error("expected `step_by` or `rev`"));
+            }
+        }
+        range
+    } else {
+        parse_range(input)?
+    };
+
+    // `0..64 step 8`
+    if input.peek(kw::step) {
+        input.parse::<kw::step>()?;
+        range.adapters.push(Adapter::StepBy(parse_step(input)?));
+    }
+
+    if rev {
+        range.adapters.push(Adapter::Rev);
+    }
+
+    Ok(range)
+}
+
+/// Parse `[A, B, C]` into its comma-separated elements. Commas inside angle
+/// brackets do not split, so `[Vec<u8>, HashMap<K, V>]` has two elements.
+fn parse_list(input: syn::parse::ParseStream) -> syn::Result<Vec<TokenStream2>> {
+    let content;
+    let brackets = syn::bracketed!(content in input);
+    let tokens: TokenStream2 = content.parse()?;
+
+    let elements = split_commas(tokens);
+    if elements.iter().any(TokenStream2::is_empty) {
+        return Err(syn::Error::new(
+            brackets.span.join(),
+            "empty element in seq! list",
+        ));
+    }
+    Ok(elements)
+}
+
+/// Split tokens at commas outside of groups and angle brackets. A trailing
+/// comma does not produce an empty last element.
+pub(crate) fn split_commas(tokens: TokenStream2) -> Vec<TokenStream2> {
+    let mut elements = Vec::new();
+    let mut element = TokenStream2::new();
+    let mut angle_depth = 0usize;
+    let mut prev_joint = None;
+    for tt in tokens {
+        if let TokenTree::Punct(p) = &tt {
+            match p.as_char() {
+                ',' if angle_depth == 0 => {
+                    elements.push(std::mem::take(&mut element));
+                    prev_joint = None;
+                    continue;
+                }
+                '<' => angle_depth += 1,
+                // `->` and `=>` are not closing brackets
+                '>' if !matches!(prev_joint, Some('-' | '=')) => {
+                    angle_depth = angle_depth.saturating_sub(1);
+                }
+                _ => {}
+            }
+            prev_joint = (p.spacing() == Spacing::Joint).then(|| p.as_char());
+        } else {
+            prev_joint = None;
+        }
+        element.extend([tt]);
+    }
+    if !element.is_empty() {
+        elements.push(element);
+    }
+    elements
+}
+
+/// Parse `START..END` or `START..=END`
+fn parse_range(input: syn::parse::ParseStream) -> syn::Result<SeqRange> {
+    let begin = input.cursor();
+    let range = if input.peek(syn::LitChar) {
+        parse_char_range(input)?
+    } else {
+        parse_int_range(input)?
+    };
+    range.check_order(tokens_between(begin, input.cursor()))?;
+    Ok(range)
+}
+
+fn parse_int_range(input: syn::parse::ParseStream) -> syn::Result<SeqRange> {
+    let span = input.span();
+    let start = parse_bound(input)?;
+
+    // Check for inclusive or exclusive range
+    let inclusive = if input.peek(Token![..=]) {
+        input.parse::<Token![..=]>()?;
+        true
+    } else {
+        input.parse::<Token![..]>()?;
+        false
+    };
+
+    let end = parse_bound(input)?;
+
+    Ok(SeqRange {
+        start: start.value,
+        end: end.value,
+        inclusive,
+        chars: false,
+        adapters: Vec::new(),
+        format: start.format.merge(end.format, span)?,
+    })
+}
+
+/// Parse `'a'..'z'` or `'a'..='z'`
+fn parse_char_range(input: syn::parse::ParseStream) -> syn::Result<SeqRange> {
+    let start: syn::LitChar = input.parse()?;
+    let inclusive = if input.peek(Token![..=]) {
+        input.parse::<Token![..=]>()?;
+        true
+    } else {
+        input.parse::<Token![..]>()?;
+        false
+    };
+    if !input.peek(syn::LitChar) {
+        return Err(input.error("expected a character literal to end the range"));
+    }
+    let end: syn::LitChar = input.parse()?;
+
+    Ok(SeqRange {
+        start: u32::from(start.value()).into(),
+        end: u32::from(end.value()).into(),
+        inclusive,
+        chars: true,
+        adapters: Vec::new(),
+        format: LitFormat::default(),
+    })
+}
+
+/// Whether a leading parenthesized group is a whole range like `(0..64)`,
+/// as opposed to a parenthesized bound like `(1 << 4)..32`
+fn is_parenthesized_range(input: syn::parse::ParseStream) -> bool {
+    let Some((TokenTree::Group(group), _)) = input.cursor().token_tree() else {
+        return false;
+    };
+    let parse_whole_range = |content: syn::parse::ParseStream| {
+        parse_range(content)?;
+        if content.is_empty() {
+            Ok(())
+        } else {
+            Err(content.error("unexpected token after range"))
+        }
+    };
+    syn::parse::Parser::parse2(parse_whole_range, group.stream()).is_ok()
+}
+
+/// Parse a bound: an integer literal or a constant expression such as `4 * 8`
+fn parse_bound(input: syn::parse::ParseStream) -> syn::Result<eval::Evaluated> {
+    eval::parse_expr(input)
+}
+
+/// Parse the step size, which must be positive
+fn parse_step(input: syn::parse::ParseStream) -> syn::Result<usize> {
+    let span = input.span();
+    let step = eval::parse_expr(input)?.value;
+    if step <= 0 {
+        return Err(syn::Error::new(span, "step must be greater than zero"));
+    }
+    usize::try_from(step).map_err(|_| syn::Error::new(span, "step is too large"))
+}
+
+/// Expand a `seq!(N in 0..8 { ... })` invocation
+pub(crate) fn seq(input: TokenStream2) -> syn::Result<TokenStream2> {
+    let input: SeqInput = syn::parse2(input)?;
+    let iterations = iterations(&input.loops);
+    expand_body(&input.loops, &input.body, &iterations)
+}
+
+/// Check that a body uses the loop variables, then expand it once for every
+/// iteration
+pub(crate) fn expand_body(
+    loops: &[SeqLoop],
+    body: &TokenStream2,
+    iterations: &[Vec<Binding>],
+) -> syn::Result<TokenStream2> {
+    let template = template_for(body, iterations);
+    check_vars_used(loops, body, &template)?;
+    expand(&template, iterations)
+}
+
+/// Expand a prepared body once for every iteration
+fn expand(template: &Template, iterations: &[Vec<Binding>]) -> syn::Result<TokenStream2> {
+    if template.has_repeat() {
+        // Only repeat the #(...) sections
+        return expand_with_repeat(&template.to_stream(), iterations);
+    }
+
+    // Repeat the entire body for each value
+    let mut output = TokenStream2::new();
+    for (index, bindings) in iterations.iter().enumerate() {
+        let position = Position {
+            index,
+            count: iterations.len(),
+        };
+        output.extend(substitute(template, bindings, Some(position))?);
+    }
+    Ok(output)
+}
+
+/// Prepare tokens for substitution in each of the iterations
+pub(crate) fn template_for(tokens: &TokenStream2, iterations: &[Vec<Binding>]) -> Template {
+    let vars: Vec<&Ident> = match iterations.first() {
+        Some(bindings) => bindings.iter().map(|binding| binding.var).collect(),
+        None => Vec::new(),
+    };
+    Template::new(tokens, &vars)
+}
+
+/// Consume the `!` and input group if `ident` starts a nested `seq!`
+/// invocation. Its body belongs to the inner macro, so repeat sections in it
+/// are not ours to expand.
+fn take_nested_seq(ident: &Ident, iter: &mut TokenIter) -> Option<(TokenTree, Group)> {
+    if ident != "seq" {
+        return None;
+    }
+    let mut lookahead = iter.clone();
+    let Some(TokenTree::Punct(bang)) = lookahead.next() else {
+        return None;
+    };
+    if bang.as_char() != '!' {
+        return None;
+    }
+    let Some(TokenTree::Group(group)) = lookahead.next() else {
+        return None;
+    };
+    iter.next(); // consume !
+    iter.next(); // consume group
+    Some((TokenTree::Punct(bang), group))
+}
+
+/// The variables a nested `seq!` declares in its header, e.g. `C` in
+/// `C in 0..4 { ... }`, which shadow outer variables of the same name
+fn nested_seq_vars(tokens: &TokenStream2) -> Vec<Ident> {
+    let mut vars = Vec::new();
+    let mut iter = tokens.clone().into_iter().peekable();
+    while let Some(tt) = iter.next() {
+        match (&tt, iter.peek()) {
+            (TokenTree::Group(g), _) if g.delimiter() == Delimiter::Brace => break,
+            (TokenTree::Ident(var), Some(TokenTree::Ident(kw))) if kw == "in" => {
+                vars.push(var.clone());
+            }
+            _ => {}
+        }
+    }
+    vars
+}
+
+/// Rebuild a group around new contents, keeping its delimiter and span
+fn with_stream(group: &Group, stream: TokenStream2) -> TokenTree {
+    let mut new_group = Group::new(group.delimiter(), stream);
+    new_group.set_span(group.span());
+    TokenTree::Group(new_group)
+}
+
+/// A repeat section `#(...)*`, `#(...)+` or either with a separator between
+/// the repetitions, such as `#(...),*` or `#(...)=>+`
+pub(crate) struct Repeat {
+    body: Group,
+    separator: Vec<TokenTree>,
+    /// `+` requires at least one iteration
+    at_least_once: bool,
+}
+
+/// Consume a repeat section following a `#`, if there is one
+pub(crate) fn take_repeat(iter: &mut TokenIter) -> Option<Repeat> {
+    let is_repeat_op =
+        |tt: &TokenTree| matches!(tt, TokenTree::Punct(p) if matches!(p.as_char(), '*' | '+'));
+
+    let mut lookahead = iter.clone();
+    let Some(TokenTree::Group(body)) = lookahead.next() else {
+        return None;
+    };
+    if body.delimiter() != Delimiter::Parenthesis {
+        return None;
+    }
+
+    // The separator is a single identifier, literal or punctuation, or two
+    // joined punctuation characters like `=>`. A `*` or `+` directly followed
+    // by another is itself the separator, as in `#(N)+*`.
+    let first = lookahead.next()?;
+    let joined = matches!(&first, TokenTree::Punct(p) if p.spacing() == Spacing::Joint);
+    let mut separator = Vec::new();
+    let op = if is_repeat_op(&first) && !(joined && lookahead.peek().is_some_and(is_repeat_op)) {
+        first
+    } else {
+        if let TokenTree::Group(_) = first {
+            return None;
+        }
+        separator.push(first);
+        if joined && lookahead.peek().is_some_and(|next| !is_repeat_op(next)) {
+            separator.push(lookahead.next()?);
+        }
+        lookahead.next()?
+    };
+    if !is_repeat_op(&op) {
+        return None;
+    }
+    let at_least_once = op.to_string() == "+";
+    *iter = lookahead;
+    Some(Repeat {
+        body,
+        separator,
+        at_least_once,
+    })
+}
+
+/// Expand a token stream that may contain repeat sections
+fn expand_with_repeat(
+    tokens: &TokenStream2,
+    iterations: &[Vec<Binding>],
+) -> syn::Result<TokenStream2> {
+    let mut output = Vec::new();
+    let tokens: Vec<TokenTree> = tokens.clone().into_iter().collect();
+    let mut iter = TokenIter::new(&tokens);
+
+    while let Some(tt) = iter.next() {
+        match &tt {
+            TokenTree::Punct(p) if p.as_char() == '#' => {
+                if let Some(TokenTree::Ident(kw)) = iter.peek() {
+                    if kw == "if" {
+                        return Err(syn::Error::new(
+                            kw.span(),
+                            "#if needs the values of an iteration, so it must be inside a \
+                             #(...) section when the body has one",
+                        ));
+                    }
+                }
+                let Some(repeat) = take_repeat(&mut iter) else {
+                    output.push(tt);
+                    continue;
+                };
+                if repeat.at_least_once && iterations.is_empty() {
+                    return Err(syn::Error::new(
+                        repeat.body.span(),
+                        "#(...)+ must repeat at least once, but the range is empty",
+                    ));
+                }
+
+                let template = template_for(&repeat.body.stream(), iterations);
+                for (index, bindings) in iterations.iter().enumerate() {
+                    if index > 0 {
+                        output.extend(repeat.separator.iter().cloned());
+                    }
+                    let position = Position {
+                        index,
+                        count: iterations.len(),
+                    };
+                    let expanded = substitute(&template, bindings, Some(position))?;
+                    output.extend(expanded);
+                }
+            }
+            TokenTree::Ident(ident) => {
+                // Leave nested seq! invocations for the inner macro
+                output.push(tt.clone());
+                if let Some((bang, group)) = take_nested_seq(ident, &mut iter) {
+                    output.push(bang);
+                    output.push(TokenTree::Group(group));
+                }
+            }
+            TokenTree::Group(g) => {
+                // Recurse into groups
+                let inner = expand_with_repeat(&g.stream(), iterations)?;
+                output.push(with_stream(g, inner));
+            }
+            _ => {
+                output.push(tt);
+            }
+        }
+    }
+
+    Ok(output.into_iter().collect())
+}
+
+/// Where the current repetition is, for the `#index`, `#count`, `#is_first`
+/// and `#is_last` helpers
+#[derive(Clone, Copy)]
+pub(crate) struct Position {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl Position {
+    /// The value of a helper such as `#index`, if `name` is one
+    fn helper(self, name: &Ident) -> Option<TokenTree> {
+        let value = if name == "index" {
+            Literal::usize_unsuffixed(self.index).into()
+        } else if name == "count" {
+            Literal::usize_unsuffixed(self.count).into()
+        } else if name == "is_first" {
+            Ident::new(&(self.index == 0).to_string(), name.span()).into()
+        } else if name == "is_last" {
+            Ident::new(&(self.index + 1 == self.count).to_string(), name.span()).into()
+        } else {
+            return None;
+        };
+        Some(value)
+    }
+}
+
+/// Replace variable occurrences with their values in this iteration
+///
+/// The helpers like `#index` and `#if` blocks are handled too if `position`
+/// is given. Those in a nested seq! are left for the inner macro.
+pub(crate) fn replace_var(
+    tokens: &TokenStream2,
+    bindings: &[Binding],
+    position: Option<Position>,
+) -> syn::Result<TokenStream2> {
+    let vars: Vec<&Ident> = bindings.iter().map(|binding| binding.var).collect();
+    substitute(&Template::new(tokens, &vars), bindings, position)
+}
+
+/// Substitute one iteration into a prepared template, see `replace_var`
+pub(crate) fn substitute(
+    template: &Template,
+    bindings: &[Binding],
+    position: Option<Position>,
+) -> syn::Result<TokenStream2> {
+    if !template.is_active() {
+        return Ok(template.to_stream());
+    }
+
+    let mut output = Vec::new();
+    let mut iter = template.iter();
+
+    while let Some(tt) = iter.next() {
+        match &tt {
+            TokenTree::Ident(ident) => {
+                // A nested seq! sees our variables, except those it redeclares
+                if let Some((bang, group)) = take_nested_seq(ident, &mut iter) {
+                    let shadowed = nested_seq_vars(&group.stream());
+                    let visible: Vec<Binding> = bindings
+                        .iter()
+                        .filter(|binding| !shadowed.contains(binding.var))
+                        .copied()
+                        .chain(shadowed.iter().map(|var| Binding { var, value: None }))
+                        .collect();
+                    output.push(tt.clone());
+                    output.push(bang);
+                    let inner = replace_var(&group.stream(), &visible, None)?;
+                    output.push(with_stream(&group, inner));
+                    continue;
+                }
+
+                // Check for identifier pasting: prefix~VAR~suffix
+                if let Some(pasted) = paste_ident(ident, &mut iter, bindings)? {
+                    output.push(TokenTree::Ident(pasted));
+                    continue;
+                }
+
+                // Check if this is just the variable itself
+                match find_value(bindings, ident) {
+                    Some(Value::Int(value, format)) => {
+                        let format = match take_format_spec(&mut iter)? {
+                            Some(spec) => format.with_spec(&spec),
+                            None => format.clone(),
+                        };
+                        output.push(value_token(value, &format, ident.span()));
+                    }
+                    Some(Value::Char(c)) => {
+                        let mut lit = Literal::character(c);
+                        lit.set_span(ident.span());
+                        output.push(TokenTree::Literal(lit));
+                    }
+                    Some(Value::Tokens(tokens)) => output.extend(tokens.clone()),
+                    None => output.push(tt),
+                }
+            }
+            TokenTree::Literal(lit) => {
+                // Check for pasting into a string: "field_~N"
+                match paste_string(lit, bindings) {
+                    Some(pasted) => output.push(TokenTree::Literal(pasted)),
+                    None => output.push(tt),
+                }
+            }
+            TokenTree::Punct(p) if p.as_char() == '#' => {
+                if let Some(position) = position {
+                    if let Some(branch) = take_conditional(&mut iter, bindings, position, false)? {
+                        output.extend(branch);
+                        continue;
+                    }
+                }
+                let helper = match (position, iter.peek()) {
+                    (Some(position), Some(TokenTree::Ident(name))) => position.helper(name),
+                    _ => None,
+                };
+                match helper {
+                    Some(mut value) => {
+                        value.set_span(iter.next().unwrap().span());
+                        output.push(value);
+                    }
+                    None => output.push(tt),
+                }
+            }
+            TokenTree::Group(g) => {
+                // Recurse into groups that have anything to substitute
+                match template.group(iter.last_index()) {
+                    Some(inner) => {
+                        output.push(with_stream(g, substitute(inner, bindings, position)?));
+                    }
+                    None => output.push(tt),
+                }
+            }
+            _ => {
+                output.push(tt);
+            }
+        }
+    }
+
+    Ok(output.into_iter().collect())
+}
+
+/// Consume a conditional block following a `#`, such as
+/// `#if(N % 2 == 0) { ... } else { ... }`, and return the contents of the
+/// branch taken in this iteration, if any. `else #if(...)` chains are allowed.
+///
+/// With `skip`, the block is consumed without evaluating anything because an
+/// earlier branch of the chain was taken.
+fn take_conditional(
+    iter: &mut TokenIter,
+    bindings: &[Binding],
+    position: Position,
+    skip: bool,
+) -> syn::Result<Option<TokenStream2>> {
+    match iter.peek() {
+        Some(TokenTree::Ident(kw)) if kw == "if" => {}
+        _ => return Ok(None),
+    }
+    let kw = iter.next().unwrap();
+
+    let (Some(TokenTree::Group(condition)), Some(TokenTree::Group(block))) =
+        (iter.next(), iter.next())
+    else {
+        return Err(syn::Error::new(kw.span(), "expected `#if(condition) { ... }`"));
+    };
+    if condition.delimiter() != Delimiter::Parenthesis || block.delimiter() != Delimiter::Brace {
+        return Err(syn::Error::new(kw.span(), "expected `#if(condition) { ... }`"));
+    }
+
+    let taken = !skip && {
+        let tokens = replace_var(&condition.stream(), bindings, Some(position))?;
+        match syn::parse::Parser::parse2(eval::parse_condition, tokens) {
+            Ok(taken) => taken,
+            Err(err) => {
+                let message = format!("{} ({})", err, iteration_note(bindings));
+                return Err(syn::Error::new(err.span(), message));
+            }
+        }
+    };
+
+    let mut output = TokenStream2::new();
+    if taken {
+        output = replace_var(&block.stream(), bindings, Some(position))?;
+    }
+
+    if let Some(TokenTree::Ident(kw)) = iter.peek() {
+        if kw == "else" {
+            let kw = iter.next().unwrap();
+            let skip = skip || taken;
+            match iter.next() {
+                Some(TokenTree::Group(block)) if block.delimiter() == Delimiter::Brace => {
+                    if !skip {
+                        output = replace_var(&block.stream(), bindings, Some(position))?;
+                    }
+                }
+                Some(TokenTree::Punct(p)) if p.as_char() == '#' => {
+                    match take_conditional(iter, bindings, position, skip)? {
+                        Some(branch) => output.extend(branch),
+                        None => return Err(else_error(&kw)),
+                    }
+                }
+                _ => return Err(else_error(&kw)),
+            }
+        }
+    }
+
+    Ok(Some(output))
+}
+
+fn else_error(kw: &TokenTree) -> syn::Error {
+    syn::Error::new(kw.span(), "expected `{ ... }` or `#if` after `else`")
+}
+
+/// The binding for a loop variable; inner variables shadow outer ones
+fn find_binding<'a>(bindings: &'a [Binding<'a>], ident: &Ident) -> Option<&'a Binding<'a>> {
+    bindings.iter().rev().find(|binding| binding.var == ident)
+}
+
+/// The value of a loop variable, unless it belongs to a nested seq!
+fn find_value<'a>(bindings: &'a [Binding<'a>], ident: &Ident) -> Option<Value<'a>> {
+    find_binding(bindings, ident)?.value
+}
+
+/// Paste a chain of identifiers joined by `~`, such as `field_~N~_mask`,
+/// `N~_mask` or the `a~N` of a lifetime `'a~N`. Returns `None`, consuming
+/// nothing, unless the chain involves one of our variables. A variable of a
+/// nested seq! ends the chain so that the inner macro can finish pasting.
+fn paste_ident(
+    first: &Ident,
+    iter: &mut TokenIter,
+    bindings: &[Binding],
+) -> syn::Result<Option<Ident>> {
+    let is_deferred = |ident: &Ident| {
+        matches!(
+            find_binding(bindings, ident),
+            Some(Binding { value: None, .. })
+        )
+    };
+    if is_deferred(first) {
+        return Ok(None);
+    }
+
+    // Collect the segments on a lookahead so nothing is consumed on failure
+    let mut lookahead = iter.clone();
+    let mut segments = Vec::new();
+    let mut segment = first.clone();
+    loop {
+        let spec = match find_value(bindings, &segment) {
+            Some(Value::Int(..)) => take_format_spec(&mut lookahead)?,
+            _ => None,
+        };
+        segments.push((segment, spec));
+
+        let mut next = lookahead.clone();
+        match (next.next(), next.next()) {
+            (Some(TokenTree::Punct(tilde)), Some(TokenTree::Ident(ident)))
+                if tilde.as_char() == '~' && !is_deferred(&ident) =>
+            {
+                lookahead = next;
+                segment = ident;
+            }
+            _ => break,
+        }
+    }
+    if segments.len() < 2
+        || segments
+            .iter()
+            .all(|(ident, _)| find_value(bindings, ident).is_none())
+    {
+        return Ok(None);
+    }
+
+    let mut name = String::new();
+    for (ident, spec) in &segments {
+        match find_value(bindings, ident) {
+            Some(Value::Int(value, _)) if value < 0 => {
+                let message = format!(
+                    "cannot paste negative value {} into an identifier ({})",
+                    value,
+                    iteration_note(bindings)
+                );
+                return Err(syn::Error::new(ident.span(), message));
+            }
+            Some(Value::Int(value, _)) => {
+                name.push_str(&LitFormat::ident_text(spec.as_ref(), value.unsigned_abs()));
+            }
+            Some(Value::Char(c)) => name.push(c),
+            Some(Value::Tokens(tokens)) => match single_ident(tokens) {
+                Some(element) => name.push_str(&element.to_string()),
+                None => {
+                    let message = format!(
+                        "cannot paste `{}` into an identifier, only single identifiers can be \
+                         pasted ({})",
+                        tokens,
+                        iteration_note(bindings)
+                    );
+                    return Err(syn::Error::new(ident.span(), message));
+                }
+            },
+            None => name.push_str(&ident.to_string()),
+        }
+    }
+    if !is_ident(&name) {
+        let message = format!(
+            "pasting produces `{}`, which is not a valid identifier ({})",
+            name,
+            iteration_note(bindings)
+        );
+        return Err(syn::Error::new(first.span(), message));
+    }
+    *iter = lookahead;
+    Ok(Some(Ident::new(&name, first.span())))
+}
+
+/// Which iteration an error happened in, e.g. `in the iteration where R = 1, C = 3`.
+/// This only covers errors seq! reports itself; errors the compiler finds in
+/// the expanded code point at the body without naming the iteration.
+fn iteration_note(bindings: &[Binding]) -> String {
+    let values: Vec<String> = bindings
+        .iter()
+        .filter_map(|binding| {
+            let value = match binding.value? {
+                Value::Int(value, _) => value.to_string(),
+                Value::Char(c) => format!("{:?}", c),
+                Value::Tokens(tokens) => tokens.to_string(),
+            };
+            Some(format!("{} = {}", binding.var, value))
+        })
+        .collect();
+    format!("in the iteration where {}", values.join(", "))
+}
+
+/// The identifier a list element consists of, if it is just one
+fn single_ident(tokens: &TokenStream2) -> Option<Ident> {
+    let mut iter = tokens.clone().into_iter();
+    match (iter.next(), iter.next()) {
+        (Some(TokenTree::Ident(ident)), None) => Some(ident),
+        _ => None,
+    }
+}
+
+fn is_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    name != "_" && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Substitute `~VAR` inside a string literal, e.g. `"field_~N"`. A `~` right
+/// after the variable name ends it, so `"field_~N~_mask"` gives `field_3_mask`.
+fn paste_string(lit: &Literal, bindings: &[Binding]) -> Option<Literal> {
+    let text = lit.to_string();
+    if !text.contains('~') || !(text.starts_with('"') || text.starts_with('r')) {
+        return None;
+    }
+    let lit_str: syn::LitStr = syn::parse2(TokenTree::Literal(lit.clone()).into()).ok()?;
+    let value = lit_str.value();
+
+    let mut pasted = String::new();
+    let mut pasted_var = false;
+    let mut rest = value.as_str();
+    while let Some(tilde) = rest.find('~') {
+        pasted.push_str(&rest[..tilde]);
+        let after = &rest[tilde + 1..];
+        let name_len = after
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        let name = &after[..name_len];
+        let bound = bindings
+            .iter()
+            .rev()
+            .find(|binding| !name.is_empty() && binding.var == name)
+            .and_then(|binding| binding.value);
+        match bound {
+            Some(value) => {
+                match value {
+                    Value::Int(value, _) => pasted.push_str(&value.to_string()),
+                    Value::Char(c) => pasted.push(c),
+                    Value::Tokens(tokens) => pasted.push_str(&tokens.to_string()),
+                }
+                pasted_var = true;
+                rest = &after[name_len..];
+                rest = rest.strip_prefix('~').unwrap_or(rest);
+            }
+            None => {
+                pasted.push('~');
+                rest = after;
+            }
+        }
+    }
+    pasted.push_str(rest);
+
+    if !pasted_var {
+        return None;
+    }
+    let mut new_lit = Literal::string(&pasted);
+    new_lit.set_span(lit.span());
+    Some(new_lit)
+}
+
+/// Consume a `~[spec]` such as `~[02]` or `~[hex]` following the variable, if
+/// any. A `~` followed by brackets is never Rust code, so the spec cannot take
+/// what follows the variable, like the `: X` of `field~N: X`.
+fn take_format_spec(iter: &mut TokenIter) -> syn::Result<Option<FormatSpec>> {
+    let mut lookahead = iter.clone();
+    let (Some(TokenTree::Punct(tilde)), Some(TokenTree::Group(group))) =
+        (lookahead.next(), lookahead.next())
+    else {
+        return Ok(None);
+    };
+    if tilde.as_char() != '~' || group.delimiter() != Delimiter::Bracket {
+        return Ok(None);
+    }
+
+    let mut tokens = group.stream().into_iter();
+    let spec = match (tokens.next(), tokens.next()) {
+        (Some(tt), None) => FormatSpec::from_token(&tt),
+        _ => None,
+    };
+    let Some(spec) = spec else {
+        return Err(syn::Error::new(
+            group.span(),
+            "expected a format spec such as `[02]`, `[hex]` or `[04x]`",
+        ));
+    };
+    *iter = lookahead;
+    Ok(Some(spec))
+}
+
+/// The literal for a loop value. Negative values are wrapped in an invisible
+/// group so that `N.pow(2)` or `x - N` treat it as a single operand.
+fn value_token(value: i128, format: &LitFormat, span: Span) -> TokenTree {
+    let lit = format.literal(value.unsigned_abs(), span);
+    if value >= 0 {
+        return TokenTree::Literal(lit);
+    }
+
+    let mut minus = Punct::new('-', Spacing::Alone);
+    minus.set_span(span);
+    let tokens: TokenStream2 = [TokenTree::Punct(minus), TokenTree::Literal(lit)]
+        .into_iter()
+        .collect();
+    let mut group = Group::new(Delimiter::None, tokens);
+    group.set_span(span);
+    TokenTree::Group(group)
+}
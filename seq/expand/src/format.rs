@@ -0,0 +1,185 @@
+// How loop values are written back out as tokens.
+//
+// A range remembers the integer suffix and radix of its literals, so that
+// `0u8..16u8` substitutes `u8` literals and `0x00..0x10` substitutes hex ones.
+// Each use of the variable may override the radix and zero-pad the digits with
+// a `N~[spec]` suffix, e.g. `reg~N~[02]` or `N~[hex]`.
+
+use proc_macro2::{Literal, Span, TokenTree};
+use syn::{Error, LitInt, Result};
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub(crate) enum Radix {
+    #[default]
+    Decimal,
+    Hex,
+    UpperHex,
+    Octal,
+    Binary,
+}
+
+impl Radix {
+    /// The radix a literal was written in, e.g. `0x10` is hex
+    fn of_literal(lit: &LitInt) -> Self {
+        let text = lit.to_string();
+        if text.starts_with("0x") {
+            Radix::Hex
+        } else if text.starts_with("0o") {
+            Radix::Octal
+        } else if text.starts_with("0b") {
+            Radix::Binary
+        } else {
+            Radix::Decimal
+        }
+    }
+
+    fn prefix(self) -> &'static str {
+        match self {
+            Radix::Decimal => "",
+            Radix::Hex | Radix::UpperHex => "0x",
+            Radix::Octal => "0o",
+            Radix::Binary => "0b",
+        }
+    }
+}
+
+/// Radix, zero-padded width and integer suffix of a substituted value
+#[derive(Clone, Default)]
+pub(crate) struct LitFormat {
+    pub radix: Radix,
+    pub width: usize,
+    pub suffix: String,
+}
+
+impl LitFormat {
+    /// The format a bound literal was written in
+    pub(crate) fn of_literal(lit: &LitInt) -> Self {
+        LitFormat {
+            radix: Radix::of_literal(lit),
+            width: 0,
+            suffix: lit.suffix().to_owned(),
+        }
+    }
+
+    /// Combine the formats of two literals in one range. The leftmost radix
+    /// wins and the suffixes must agree if both are present.
+    pub(crate) fn merge(self, other: LitFormat, span: Span) -> Result<LitFormat> {
+        let suffix = match (self.suffix.is_empty(), other.suffix.is_empty()) {
+            (_, true) => self.suffix,
+            (true, false) => other.suffix,
+            (false, false) if self.suffix == other.suffix => self.suffix,
+            (false, false) => {
+                return Err(Error::new(
+                    span,
+                    format!(
+                        "mismatched literal suffixes `{}` and `{}`",
+                        self.suffix, other.suffix
+                    ),
+                ));
+            }
+        };
+        let radix = if self.radix == Radix::Decimal {
+            other.radix
+        } else {
+            self.radix
+        };
+        Ok(LitFormat {
+            radix,
+            width: 0,
+            suffix,
+        })
+    }
+
+    /// Apply a per-use `:spec`, keeping the range's suffix
+    pub(crate) fn with_spec(&self, spec: &FormatSpec) -> LitFormat {
+        LitFormat {
+            radix: spec.radix.unwrap_or(self.radix),
+            width: spec.width,
+            suffix: self.suffix.clone(),
+        }
+    }
+
+    /// Digits of `value` without prefix, sign or suffix, zero-padded to width
+    fn digits(&self, value: u128) -> String {
+        let width = self.width;
+        match self.radix {
+            Radix::Decimal => format!("{:0width$}", value),
+            Radix::Hex => format!("{:0width$x}", value),
+            Radix::UpperHex => format!("{:0width$X}", value),
+            Radix::Octal => format!("{:0width$o}", value),
+            Radix::Binary => format!("{:0width$b}", value),
+        }
+    }
+
+    /// Text pasted into an identifier: digits only, in the per-use radix
+    pub(crate) fn ident_text(spec: Option<&FormatSpec>, value: u128) -> String {
+        let format = LitFormat {
+            radix: spec.and_then(|spec| spec.radix).unwrap_or_default(),
+            width: spec.map_or(0, |spec| spec.width),
+            suffix: String::new(),
+        };
+        format.digits(value)
+    }
+
+    /// The literal token for a non-negative value
+    pub(crate) fn literal(&self, value: u128, span: Span) -> Literal {
+        let text = format!(
+            "{}{}{}",
+            self.radix.prefix(),
+            self.digits(value),
+            self.suffix
+        );
+        let mut lit: Literal = text.parse().expect("formatted integer literal");
+        lit.set_span(span);
+        lit
+    }
+}
+
+/// A per-use format such as `N~[02]`, `N~[hex]` or `N~[04x]`
+pub(crate) struct FormatSpec {
+    radix: Option<Radix>,
+    width: usize,
+}
+
+impl FormatSpec {
+    /// Interpret the token in the brackets of `N~[...]`; `None` if it is not a
+    /// format spec
+    pub(crate) fn from_token(tt: &TokenTree) -> Option<FormatSpec> {
+        match tt {
+            TokenTree::Ident(ident) => {
+                let radix = radix_from_name(&ident.to_string())?;
+                Some(FormatSpec {
+                    radix: Some(radix),
+                    width: 0,
+                })
+            }
+            TokenTree::Literal(lit) => {
+                // `02`, `8`, or with a radix suffix like `04x`
+                let text = lit.to_string();
+                let digits_end = text
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(text.len());
+                let (digits, rest) = text.split_at(digits_end);
+                let width = digits.parse().ok()?;
+                let radix = if rest.is_empty() {
+                    None
+                } else {
+                    Some(radix_from_name(rest)?)
+                };
+                Some(FormatSpec { radix, width })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn radix_from_name(name: &str) -> Option<Radix> {
+    match name {
+        "dec" | "d" => Some(Radix::Decimal),
+        "hex" | "x" => Some(Radix::Hex),
+        "HEX" | "X" => Some(Radix::UpperHex),
+        "oct" | "o" => Some(Radix::Octal),
+        "bin" | "b" => Some(Radix::Binary),
+        _ => None,
+    }
+}
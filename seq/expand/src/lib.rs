@@ -0,0 +1,22 @@
+// The expansion of seq! and #[seq::repeat], kept out of the proc-macro crate so
+// that it can also be driven outside the compiler, as the benchmark does. The
+// macros themselves only hand their input over.
+
+use proc_macro2::TokenStream;
+use syn::Result;
+
+mod attr;
+mod eval;
+mod expand;
+mod format;
+mod template;
+
+/// Expand a `seq!(N in 0..8 { ... })` invocation
+pub fn seq(input: TokenStream) -> Result<TokenStream> {
+    expand::seq(input)
+}
+
+/// Expand `#[seq::repeat(N in 0..8)]` on an item
+pub fn repeat(args: TokenStream, item: TokenStream) -> Result<TokenStream> {
+    attr::repeat(args, item)
+}
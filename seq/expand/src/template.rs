@@ -0,0 +1,124 @@
+// Bodies prepared once for substitution in every iteration.
+//
+// For a 256-entry table the same body is substituted 256 times, and most of it
+// usually has nothing to substitute. A Template records once which groups
+// contain a loop variable, helper, paste or nested seq!, so that the other
+// groups are copied whole instead of being walked again in every iteration. It
+// also records whether the body has repeat sections, which decides how it is
+// expanded.
+// The tokens are kept in a Vec so that looking ahead only copies a position.
+
+use proc_macro2::{Ident, TokenStream as TokenStream2, TokenTree};
+
+use crate::expand::take_repeat;
+
+pub(crate) struct Template {
+    tokens: Vec<TokenTree>,
+    /// The template of each group token that has something to substitute
+    groups: Vec<Option<Template>>,
+    /// Whether anything at this level or below may need substituting
+    active: bool,
+    /// Whether a `#(...)` repeat section appears at this level or below,
+    /// outside the bodies of nested seq! invocations
+    has_repeat: bool,
+}
+
+impl Template {
+    pub(crate) fn new(tokens: &TokenStream2, vars: &[&Ident]) -> Template {
+        let tokens: Vec<TokenTree> = tokens.clone().into_iter().collect();
+        let mut active = false;
+        let mut has_repeat = false;
+        let groups = tokens
+            .iter()
+            .enumerate()
+            .map(|(index, tt)| {
+                let (group, token_active) = match tt {
+                    TokenTree::Group(group) => {
+                        let inner = Template::new(&group.stream(), vars);
+                        has_repeat |= inner.has_repeat && !is_nested_seq_body(&tokens, index);
+                        let inner_active = inner.active;
+                        (inner_active.then_some(inner), inner_active)
+                    }
+                    TokenTree::Ident(ident) => (None, ident == "seq" || vars.contains(&ident)),
+                    TokenTree::Punct(p) => {
+                        if p.as_char() == '#' {
+                            let mut rest = TokenIter::new(&tokens[index + 1..]);
+                            has_repeat |= take_repeat(&mut rest).is_some();
+                        }
+                        (None, matches!(p.as_char(), '#' | '~'))
+                    }
+                    TokenTree::Literal(lit) => (None, lit.to_string().contains('~')),
+                };
+                active |= token_active;
+                group
+            })
+            .collect();
+        Template {
+            tokens,
+            groups,
+            active,
+            has_repeat,
+        }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub(crate) fn has_repeat(&self) -> bool {
+        self.has_repeat
+    }
+
+    pub(crate) fn iter(&self) -> TokenIter<'_> {
+        TokenIter::new(&self.tokens)
+    }
+
+    /// The template of the group at `index`, if it has anything to substitute
+    pub(crate) fn group(&self, index: usize) -> Option<&Template> {
+        self.groups[index].as_ref()
+    }
+
+    pub(crate) fn to_stream(&self) -> TokenStream2 {
+        self.tokens.iter().cloned().collect()
+    }
+}
+
+/// Whether the group at `index` is the input of a nested `seq!`, whose repeat
+/// sections belong to the inner macro
+fn is_nested_seq_body(tokens: &[TokenTree], index: usize) -> bool {
+    index >= 2
+        && matches!(&tokens[index - 2], TokenTree::Ident(ident) if ident == "seq")
+        && matches!(&tokens[index - 1], TokenTree::Punct(bang) if bang.as_char() == '!')
+}
+
+/// An iterator over tokens that is cheap to clone for lookahead
+#[derive(Clone)]
+pub(crate) struct TokenIter<'a> {
+    tokens: &'a [TokenTree],
+    pos: usize,
+}
+
+impl<'a> TokenIter<'a> {
+    pub(crate) fn new(tokens: &'a [TokenTree]) -> Self {
+        TokenIter { tokens, pos: 0 }
+    }
+
+    pub(crate) fn peek(&self) -> Option<&'a TokenTree> {
+        self.tokens.get(self.pos)
+    }
+
+    /// The index of the token most recently returned by `next`
+    pub(crate) fn last_index(&self) -> usize {
+        self.pos - 1
+    }
+}
+
+impl Iterator for TokenIter<'_> {
+    type Item = TokenTree;
+
+    fn next(&mut self) -> Option<TokenTree> {
+        let tt = self.tokens.get(self.pos)?.clone();
+        self.pos += 1;
+        Some(tt)
+    }
+}
@@ -0,0 +1,45 @@
+// Ranges are not limited to counting up by one from zero. Support negative
+// bounds, a step size, and reversed iteration:
+//
+//     seq!(N in -4..4 { ... })            // -4, -3, ..., 3
+//     seq!(N in 0..64 step 8 { ... })     // 0, 8, ..., 56
+//     seq!(N in (0..64).step_by(8) { ... })
+//     seq!(N in rev 0..8 { ... })         // 7, 6, ..., 0
+//     seq!(N in (0..8).step_by(3).rev() { ... })
+//
+// With the keyword form the step is applied before reversing. With the method
+// form the adapters apply in the order they are called, like on a real Range.
+//
+// Negative values must still behave as a single operand wherever N appears,
+// so that `N.pow(2)` squares -3 rather than negating 3 squared.
+
+use seq::seq;
+
+fn main() {
+    let signed = seq!(N in -4..4 { [#(N,)*] });
+    assert_eq!(signed, [-4, -3, -2, -1, 0, 1, 2, 3]);
+
+    let squares = seq!(N in -3..=-1 { [#((N as i32).pow(2),)*] });
+    assert_eq!(squares, [9, 4, 1]);
+
+    let stepped = seq!(N in 0..64 step 8 { [#(N,)*] });
+    assert_eq!(stepped, [0, 8, 16, 24, 32, 40, 48, 56]);
+
+    let step_by = seq!(N in (0..=10).step_by(5) { [#(N,)*] });
+    assert_eq!(step_by, [0, 5, 10]);
+
+    let reversed = seq!(N in rev 0..4 { [#(N,)*] });
+    assert_eq!(reversed, [3, 2, 1, 0]);
+
+    let rev_stepped = seq!(N in rev 0..8 step 3 { [#(N,)*] });
+    assert_eq!(rev_stepped, [6, 3, 0]);
+
+    let chained = seq!(N in (0..8).rev().step_by(3) { [#(N,)*] });
+    assert_eq!(chained, [7, 4, 1]);
+
+    let mut sum = 0;
+    seq!(N in -2..=2 {
+        sum += N;
+    });
+    assert_eq!(sum, 0);
+}
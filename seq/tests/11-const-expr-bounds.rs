@@ -0,0 +1,60 @@
+// Range bounds may be small constant expressions built from integer literals,
+// which seq! evaluates while expanding:
+//
+//     seq!(N in 0..4 * 8 { ... })
+//     seq!(N in 1..=(1 << 4) { ... })
+//
+// The operators are the integer ones Rust has (`+ - * / % << >> & ^ |`) with
+// the same precedence, plus parentheses and unary minus.
+//
+// As explained in test 09, seq! can never see the value of a const such as
+// `BITS`. Instead keep the source of truth in a macro_rules! macro that calls
+// back into whatever needs the number. Because bounds may be expressions, the
+// callback can pass it as `$bits:expr`, which arrives as an invisible group and
+// keeps its own precedence: `2 * $bits` below means `2 * (4 * 8)`.
+
+use seq::seq;
+
+// Source of truth, passed to a callback macro.
+macro_rules! with_bits {
+    ($callback:ident) => {
+        $callback! { 4 * 8 }
+    };
+}
+
+macro_rules! define_bits {
+    ($bits:expr) => {
+        const BITS: usize = $bits;
+    };
+}
+
+macro_rules! bit_masks {
+    ($bits:expr) => {
+        seq!(N in 0..$bits { [#(1u64 << N,)*] })
+    };
+}
+
+macro_rules! double_width_len {
+    ($bits:expr) => {
+        seq!(N in 0..2 * $bits { [#(N,)*] }).len()
+    };
+}
+
+with_bits!(define_bits);
+
+fn main() {
+    let masks: [u64; BITS] = with_bits!(bit_masks);
+    assert_eq!(masks[0], 1);
+    assert_eq!(masks[31], 1 << 31);
+
+    assert_eq!(with_bits!(double_width_len), 64);
+
+    let powers = seq!(N in 1..=(1 << 2) { [#(N,)*] });
+    assert_eq!(powers, [1, 2, 3, 4]);
+
+    let mixed = seq!(N in -(2 + 1)..10 % 3 + 16 / 8 { [#(N,)*] });
+    assert_eq!(mixed, [-3, -2, -1, 0, 1, 2]);
+
+    let stepped = seq!(N in (0..0xff & 0x0f).step_by(1 << 2) { [#(N,)*] });
+    assert_eq!(stepped, [0, 4, 8, 12]);
+}
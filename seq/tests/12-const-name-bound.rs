@@ -0,0 +1,13 @@
+// Naming a const in a bound cannot work, since macros expand before consts are
+// evaluated. Rather than a generic parse error, point at the name and suggest
+// the macro_rules! callback pattern from test 11.
+
+use seq::seq;
+
+const BITS: usize = 32;
+
+seq!(N in 0..BITS {
+    fn f~N() {}
+});
+
+fn main() {}
@@ -0,0 +1,66 @@
+// The substituted literal keeps the suffix and radix of the range bounds, so
+// `0u8..4u8` produces `u8` literals and `0x10..0x14` produces hex literals.
+// If only one bound has a suffix, that suffix is used for every value.
+//
+// Each use of the variable may also be followed by a `~[spec]` to choose the
+// radix and a zero-padded width, which is most useful when pasting:
+//
+//     reg~N~[02]      // reg00, reg01, ..., reg15
+//     mask~N~[hex]    // mask0, ..., maskf
+//     N~[04x]         // 0x000f
+//
+// The radix may be spelled `dec`, `hex`, `HEX`, `oct` or `bin` (or `d`, `x`,
+// `X`, `o`, `b` after a width). Pasted identifiers only take the digits, never
+// the prefix or suffix. The brackets keep a spec apart from ordinary code, so
+// `f~N: X` is still a field of type `X`.
+
+use seq::seq;
+
+seq!(N in 0..16 {
+    #[allow(non_upper_case_globals)]
+    const reg~N~[02]: u32 = N;
+    #[allow(non_upper_case_globals)]
+    const mask~N~[hex]: u32 = N;
+
+    #[allow(dead_code)]
+    struct Wrapper~N<X> {
+        field~N: X,
+    }
+});
+
+struct Foo0(u8);
+
+impl Foo0 {
+    fn new() -> Self {
+        Foo0(0)
+    }
+}
+
+fn size_of_val<T>(_: T) -> usize {
+    std::mem::size_of::<T>()
+}
+
+fn main() {
+    assert_eq!(reg07, 7);
+    assert_eq!(reg15, 15);
+    assert_eq!(maskf, 15);
+
+    // The suffix decides the type of every substituted literal.
+    let sizes = seq!(N in 0u8..4 { [#(size_of_val(N),)*] });
+    assert_eq!(sizes, [1, 1, 1, 1]);
+    let sizes = seq!(N in 0..=2usize { [#(size_of_val(N),)*] });
+    assert_eq!(sizes, [8, 8, 8]);
+
+    // The radix is kept, which shows when the tokens are turned into a string.
+    let text = seq!(N in 0x0e..0x11 { stringify!(#(N)*) });
+    assert_eq!(text, "0xe 0xf 0x10");
+
+    let text = seq!(N in 0..3 { stringify!(#(N~[02] N~[04x] N~[bin])*) });
+    assert_eq!(text, "00 0x0000 0b0 01 0x0001 0b1 02 0x0002 0b10");
+
+    // A `:` after the variable is left alone.
+    let value = seq!(N in 0..1 { Foo~N::new() });
+    assert_eq!(value.0, 0);
+    let wrapper = Wrapper3 { field3: 'b' };
+    assert_eq!(wrapper.field3, 'b');
+}
@@ -0,0 +1,43 @@
+// More than one loop variable may be given, separated by commas. The body is
+// expanded for the cartesian product of the ranges, with the last variable
+// changing fastest:
+//
+//     seq!(R in 0..2, C in 0..3 { ... })   // (0,0) (0,1) (0,2) (1,0) ...
+//
+// Pasting may be chained to use several variables in one identifier, as in
+// `cell~R~C`.
+//
+// seq! invocations may also be nested. The outer seq! substitutes its own
+// variables inside the inner invocation, so the inner body and range can refer
+// to the outer value, but it leaves the inner #(...)* sections alone for the
+// inner seq! to expand. An inner variable with the same name as an outer one
+// shadows it.
+
+use seq::seq;
+
+seq!(R in 0..2, C in 0..3 {
+    #[allow(non_upper_case_globals)]
+    const cell~R~C: (usize, usize) = (R, C);
+});
+
+fn main() {
+    assert_eq!(cell00, (0, 0));
+    assert_eq!(cell12, (1, 2));
+
+    let pairs = seq!(R in 0..2, C in 0..2 { [#((R, C),)*] });
+    assert_eq!(pairs, [(0, 0), (0, 1), (1, 0), (1, 1)]);
+
+    // The inner repeat section belongs to the inner seq!, which sees R.
+    let mut rows = Vec::new();
+    seq!(R in 1..4 {
+        rows.push(seq!(C in 0..R { vec![#(R * 10 + C,)*] }));
+    });
+    assert_eq!(rows, [vec![10], vec![20, 21], vec![30, 31, 32]]);
+
+    // The same name in the inner seq! refers to the inner variable.
+    let mut shadowed = Vec::new();
+    seq!(N in 0..2 {
+        shadowed.push(seq!(N in 5..7 { [#(N,)*] }));
+    });
+    assert_eq!(shadowed, [[5, 6], [5, 6]]);
+}
@@ -0,0 +1,55 @@
+// Pasting is not limited to a prefix followed by the variable. Any chain of
+// identifiers joined by `~` is glued together as long as one of them is a loop
+// variable, so the number may also come first or sit in the middle:
+//
+//     field_~N~_mask    // field_0_mask, field_1_mask, ...
+//     N~_th             // error: `0_th` is not a valid identifier
+//
+// Lifetimes paste the same way, `'a~N` giving `'a0`, `'a1`, ...
+//
+// Inside a string literal, `~N` is replaced by the value of N. A `~` directly
+// after the variable name ends it, which allows text to follow that would
+// otherwise be read as part of the name: "field_~N~_mask".
+
+use seq::seq;
+
+seq!(N in 0..3 {
+    #[allow(non_upper_case_globals)]
+    const field_~N~_mask: u32 = 1 << N;
+});
+
+seq!(N in 0..2 {
+    #[allow(dead_code)]
+    struct Refs<#('a~N,)*> {
+        #(r~N: &'a~N str,)*
+    }
+});
+
+seq!(N in 0..3 {
+    const NAMES: [&str; 3] = [#("field_~N",)*];
+    const MASKS: [&str; 3] = [#("field_~N~_mask",)*];
+});
+
+fn main() {
+    assert_eq!(field_0_mask, 1);
+    assert_eq!(field_2_mask, 4);
+
+    let (a, b) = (String::from("a"), String::from("b"));
+    let refs = Refs { r0: &a, r1: &b };
+    assert_eq!(refs.r0, "a");
+    assert_eq!(refs.r1, "b");
+
+    assert_eq!(NAMES, ["field_0", "field_1", "field_2"]);
+    assert_eq!(MASKS, ["field_0_mask", "field_1_mask", "field_2_mask"]);
+
+    // Nested pasting: the outer seq! pastes R and leaves C for the inner one.
+    seq!(R in 0..2 {
+        seq!(C in 0..2 {
+            #(
+                #[allow(non_upper_case_globals)]
+                const cell_~R~_~C: (u32, u32) = (R, C);
+            )*
+        });
+    });
+    assert_eq!(cell_1_0, (1, 0));
+}
@@ -0,0 +1,67 @@
+// Besides integers, a loop variable can range over characters or over an
+// explicit list of token trees:
+//
+//     seq!(L in 'a'..='f' { ... })            // 'a', 'b', ..., 'f'
+//     seq!(T in [u8, u16, u32, u64] { ... })  // u8, u16, u32, u64
+//
+// A character is substituted as a char literal and pastes as itself, so
+// `get_~L` becomes `get_a`. A list element is substituted exactly as written,
+// which makes seq! a replacement for macro_rules! that stamp out one impl per
+// type. Elements are separated by commas outside angle brackets, so
+// `Vec<(u8, u16)>` is one element. Only elements that are a single identifier
+// can be pasted into another identifier.
+
+use seq::seq;
+
+trait Width {
+    const BITS: u32;
+}
+
+seq!(T in [u8, u16, u32, u64] {
+    impl Width for T {
+        const BITS: u32 = T::BITS;
+    }
+});
+
+struct Letters;
+
+seq!(L in 'a'..='c' {
+    impl Letters {
+        #(
+            fn get_~L(&self) -> char {
+                L
+            }
+        )*
+    }
+});
+
+seq!(T in [u8, i32] {
+    #[allow(non_upper_case_globals)]
+    const max_~T: T = T::MAX;
+});
+
+fn main() {
+    assert_eq!(<u16 as Width>::BITS, 16);
+    assert_eq!(<u64 as Width>::BITS, 64);
+
+    let letters = Letters;
+    assert_eq!(letters.get_a(), 'a');
+    assert_eq!(letters.get_c(), 'c');
+
+    let vowels = seq!(L in ('a'..='z').step_by(4) { [#(L,)*] });
+    assert_eq!(vowels, ['a', 'e', 'i', 'm', 'q', 'u', 'y']);
+
+    let backwards = seq!(L in rev 'x'..'{' { [#(L,)*] });
+    assert_eq!(backwards, ['z', 'y', 'x']);
+
+    assert_eq!(max_u8, 255);
+    assert_eq!(max_i32, i32::MAX);
+
+    let lens = seq!(V in [Vec::<u8>::new(), vec![1, 2], Vec::<(u8, u16)>::new()] {
+        [#(V.len(),)*]
+    });
+    assert_eq!(lens, [0, 2, 0]);
+
+    let names = seq!(T in [u8, bool] { [#("type ~T",)*] });
+    assert_eq!(names, ["type u8", "type bool"]);
+}
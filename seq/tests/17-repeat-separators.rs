@@ -0,0 +1,66 @@
+// Like in macro_rules!, a repeat section may name a separator that goes
+// between the repetitions but not after the last one, and may use `+`
+// instead of `*` to require at least one repetition:
+//
+//     #(N),*        // 0, 1, 2
+//     #(N => N)|+   // a separator may be any single token
+//
+// Inside a repeat section, and in the body when the whole body is repeated,
+// a few helpers describe the current repetition:
+//
+//     #index       // 0, 1, 2, ...
+//     #count       // the number of repetitions
+//     #is_first    // true for the first repetition
+//     #is_last     // true for the last repetition
+//
+// A body may contain any number of repeat sections, each expanded over the
+// full range.
+
+use seq::seq;
+
+fn describe(n: u8) -> &'static str {
+    seq!(N in 0..3 {
+        match n {
+            #(N => stringify!(N)),*,
+            _ => "many",
+        }
+    })
+}
+
+seq!(N in 1..4 {
+    fn sum() -> u32 {
+        #(N)+*
+    }
+
+    fn tuple() -> (#(u8),*) {
+        (#(N),*)
+    }
+});
+
+fn main() {
+    assert_eq!(describe(1), "1");
+    assert_eq!(describe(7), "many");
+
+    assert_eq!(sum(), 6);
+    assert_eq!(tuple(), (1, 2, 3));
+
+    let squares = seq!(N in 0..4 { [#(N * N),*] });
+    assert_eq!(squares, [0, 1, 4, 9]);
+
+    let indexed = seq!(N in (10..40).step_by(10) { [#((#index, N, #count)),*] });
+    assert_eq!(indexed, [(0, 10, 3), (1, 20, 3), (2, 30, 3)]);
+
+    let flags = seq!(N in 0..3 { [#((#is_first, #is_last)),*] });
+    assert_eq!(flags, [(true, false), (false, false), (false, true)]);
+
+    let mut last = Vec::new();
+    seq!(N in 0..3 {
+        if #is_last {
+            last.push(N);
+        }
+    });
+    assert_eq!(last, [2]);
+
+    let arrays = seq!(N in 0..2 { ([#(N),*], [#(N + #count),*]) });
+    assert_eq!(arrays, ([0, 1], [2, 3]));
+}
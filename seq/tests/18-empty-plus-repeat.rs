@@ -0,0 +1,9 @@
+// A `#(...)+` section must repeat at least once, so an empty range is an error.
+
+use seq::seq;
+
+seq!(N in 0..0 {
+    const SUM: u32 = #(N)++;
+});
+
+fn main() {}
@@ -0,0 +1,25 @@
+// A range that is far larger than intended would otherwise expand to millions
+// of tokens and bring the compiler to a crawl. seq! refuses to expand to more
+// than 65536 iterations in total, counting every combination of the loop
+// variables and after any step, and nothing is expanded when the check fails.
+// The limit can be raised by ending the header with `limit = N`.
+
+use seq::seq;
+
+seq!(N in 0..1_000_000 {
+    const _: u32 = N;
+});
+
+seq!(R in 0..300, C in 0..300 {
+    const _: (u32, u32) = (R, C);
+});
+
+seq!(N in 0..1_000_000_000_000 step 1_000 {
+    const _: u64 = N;
+});
+
+seq!(N in 0..70_000, limit = 70_000 {
+    const _: u32 = N;
+});
+
+fn main() {}
@@ -0,0 +1,15 @@
+// A range whose end is before its start would expand to nothing, which is
+// almost always a mistake for a countdown. Iterating backwards is spelled
+// with `rev` instead.
+
+use seq::seq;
+
+seq!(N in 8..0 {
+    const _: u32 = N;
+});
+
+seq!(L in 'z'..='a' {
+    const _: char = L;
+});
+
+fn main() {}
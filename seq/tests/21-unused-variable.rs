@@ -0,0 +1,18 @@
+// A loop variable that the body never mentions is usually a typo, such as a
+// body that uses `n` for a variable declared as `N`. Prefix the name with an
+// underscore to repeat a body that really does not depend on it.
+
+use seq::seq;
+
+fn main() {
+    let mut count = 0;
+    seq!(_N in 0..3 {
+        count += 1;
+    });
+    assert_eq!(count, 3);
+
+    let n = 0;
+    seq!(N in 0..3 {
+        let _ = n;
+    });
+}
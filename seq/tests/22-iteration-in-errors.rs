@@ -0,0 +1,25 @@
+// Tokens copied into each iteration keep the span of the original token in the
+// body, so errors from the compiler point at the code that was written rather
+// than the whole seq! invocation. Errors that seq! itself reports while
+// expanding also name the iteration they happened in.
+//
+// Errors the compiler reports later in the expanded code only carry those
+// spans: a procedural macro has no way to attach a note to them, so they cannot
+// say which iteration produced them, and identical errors from several
+// iterations are shown once.
+
+use seq::seq;
+
+seq!(N in 0..3 {
+    #(
+        const N~_th: u32 = N;
+    )*
+});
+
+seq!(N in 0..2 {
+    fn value~N() -> u8 {
+        "N"
+    }
+});
+
+fn main() {}
@@ -0,0 +1,78 @@
+// Part of the body can be included or left out depending on the iteration:
+//
+//     #if(N % 2 == 0) { ... }
+//     #if(N == 0) { ... } else #if(N < 4) { ... } else { ... }
+//
+// The condition is evaluated while expanding, after loop variables and the
+// #index, #count, #is_first and #is_last helpers have been substituted. It
+// supports the integer operators allowed in range bounds plus comparisons,
+// `&&`, `||` and `!`, and characters compare by code point. The braces
+// around the taken branch are not part of the output, so a branch can hold
+// fields, match arms or list elements as well as statements.
+//
+// A body with #(...) sections only has iterations inside those sections, so
+// that is where #if must go in such a body.
+
+use seq::seq;
+
+seq!(N in 0..6 {
+    #[derive(Default)]
+    struct Even {
+        #(
+            #if(N % 2 == 0) {
+                field~N: u8,
+            }
+        )*
+    }
+});
+
+fn kind(n: u8) -> &'static str {
+    seq!(N in 0..6 {
+        match n {
+            #(
+                #if(N == 0) {
+                    N => "zero",
+                } else #if(N < 4) {
+                    N => "small",
+                } else {
+                    N => "large",
+                }
+            )*
+            _ => "out of range",
+        }
+    })
+}
+
+fn main() {
+    let even = Even::default();
+    let Even {
+        field0,
+        field2,
+        field4,
+    } = even;
+    assert_eq!((field0, field2, field4), (0, 0, 0));
+
+    assert_eq!(kind(0), "zero");
+    assert_eq!(kind(3), "small");
+    assert_eq!(kind(5), "large");
+    assert_eq!(kind(9), "out of range");
+
+    let skip_zero = seq!(N in 0..5 { [#(#if(N != 0) { N, })*] });
+    assert_eq!(skip_zero, [1, 2, 3, 4]);
+
+    let joined = seq!(N in 0..3 { [#(N #if(!#is_last) { , })*] });
+    assert_eq!(joined, [0, 1, 2]);
+
+    let consonants = seq!(L in 'a'..='f' {
+        [#(#if(L != 'a' && L != 'e') { L, })*]
+    });
+    assert_eq!(consonants, ['b', 'c', 'd', 'f']);
+
+    let mut odd = Vec::new();
+    seq!(N in -3..3 {
+        #if(N % 2 != 0 || N == -2) {
+            odd.push(N);
+        }
+    });
+    assert_eq!(odd, [-3, -2, -1, 1]);
+}
@@ -0,0 +1,19 @@
+// Conditions are evaluated while expanding, so they may only use loop
+// variables, helpers and literals. #if cannot appear outside the #(...)
+// sections of a body that has them, because there are no loop values there.
+
+use seq::seq;
+
+seq!(N in 0..3 {
+    #if(N < LIMIT) {
+        const _: u32 = N;
+    }
+});
+
+seq!(N in 0..3 {
+    #if(true) {
+        const A: [u32; 3] = [#(N,)*];
+    }
+});
+
+fn main() {}
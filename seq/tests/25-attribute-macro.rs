@@ -0,0 +1,107 @@
+// seq! can also be written as an attribute on the item it repeats, which keeps
+// the item visible to rustdoc and IDEs:
+//
+//     #[seq::repeat(N in 0..8)]
+//     impl Foo for Bar_N {}
+//
+// The arguments are the same header as seq! takes. Unlike a seq! body, the
+// item has to be valid Rust before it is expanded, so `Bar~N` cannot be
+// written. Instead a loop variable is pasted wherever it is one of the
+// underscore separated words of an identifier: `Bar_N` becomes `Bar_0`, and
+// `field_N_mask` becomes `field_0_mask`.
+//
+// To repeat only some of the variants of an enum or the fields of a struct,
+// mark them with a bare #[repeat]. The rest of the item is emitted once, as
+// written, so a loop variable in it is left alone.
+//
+// The attribute cannot be called #[seq] because that name is already taken by
+// the seq! macro.
+
+use seq::repeat;
+
+trait Id {
+    const ID: u32;
+}
+
+#[repeat(N in 0..3)]
+#[allow(non_camel_case_types)]
+struct Unit_N;
+
+#[repeat(N in 0..3)]
+impl Id for Unit_N {
+    const ID: u32 = N * 10;
+}
+
+/// Squares of the first few numbers
+#[seq::repeat(N in 1..4)]
+fn square_N() -> u32 {
+    N * N
+}
+
+#[repeat(N in 0..4)]
+#[derive(Debug, PartialEq)]
+enum Reg {
+    /// Stack pointer
+    Sp = 50,
+    #[repeat]
+    #[allow(non_camel_case_types)]
+    R_N = N,
+    Pc = 100,
+}
+
+#[repeat(N in 0..2)]
+#[derive(Default)]
+struct Regs<T> {
+    flags: u8,
+    /// General purpose register ~N
+    #[repeat]
+    #[allow(dead_code)]
+    r_N: Option<T>,
+}
+
+#[repeat(N in 0..2)]
+#[derive(Default)]
+#[allow(non_snake_case)]
+struct Counters {
+    count_N_total: u8,
+    #[repeat]
+    r_N: u8,
+}
+
+#[repeat(N in 0..3)]
+struct Tuple(
+    &'static str,
+    #[repeat] u32,
+);
+
+#[repeat(N in 0..2)]
+pub(crate) struct Callbacks<F: Fn(u8) -> u8>(
+    #[repeat] F,
+);
+
+#[repeat(N in 0..4)]
+#[allow(non_upper_case_globals)]
+const bit_N_mask: u8 = 1 << N;
+
+fn main() {
+    assert_eq!(Unit_2::ID, 20);
+    assert_eq!(square_3(), 9);
+
+    assert_eq!(Reg::R_2 as u8, 2);
+    assert_eq!(Reg::Pc as u8, 100);
+    assert_ne!(Reg::Sp, Reg::R_0);
+
+    let regs = Regs::<u16>::default();
+    assert_eq!((regs.flags, regs.r_0, regs.r_1), (0, None, None));
+
+    let counters = Counters::default();
+    assert_eq!((counters.count_N_total, counters.r_0, counters.r_1), (0, 0, 0));
+
+    let tuple = Tuple("regs", 0, 1, 2);
+    assert_eq!((tuple.0, tuple.3), ("regs", 2));
+
+    let callbacks = Callbacks::<fn(u8) -> u8>(|x| x + 1, |x| x * 2);
+    assert_eq!((callbacks.0(3), callbacks.1(3)), (4, 6));
+
+    assert_eq!(bit_3_mask, 0b1000);
+}
@@ -0,0 +1,13 @@
+// Only the values a range steps to are generated, so a huge range with a large
+// step is as cheap as its handful of iterations and stays within the limit.
+
+use seq::seq;
+
+fn main() {
+    let stepped: [u64; 1_000] = seq!(N in 0..1_000_000_000_000 step 1_000_000_000 { [#(N,)*] });
+    assert_eq!(stepped[1], 1_000_000_000);
+    assert_eq!(stepped[999], 999_000_000_000);
+
+    let reversed: [u64; 3] = seq!(N in (0..=1 << 60).step_by(1 << 59).rev() { [#(N,)*] });
+    assert_eq!(reversed, [1 << 60, 1 << 59, 0]);
+}
@@ -0,0 +1,10 @@
+// A `~[...]` after a loop variable is always a format spec, so anything in the
+// brackets other than a width and radix is an error rather than being pasted.
+
+use seq::seq;
+
+seq!(N in 0..4 {
+    const reg~N~[wide]: u32 = N;
+});
+
+fn main() {}
@@ -10,4 +10,22 @@ fn tests() {
     t.pass("tests/07-inclusive-range.rs");
     t.compile_fail("tests/08-ident-span.rs");
     t.pass("tests/09-interaction-with-macrorules.rs");
+    t.pass("tests/10-signed-stepped-reversed.rs");
+    t.pass("tests/11-const-expr-bounds.rs");
+    t.compile_fail("tests/12-const-name-bound.rs");
+    t.pass("tests/13-literal-format.rs");
+    t.pass("tests/14-multiple-and-nested.rs");
+    t.pass("tests/15-rich-pasting.rs");
+    t.pass("tests/16-chars-and-lists.rs");
+    t.pass("tests/17-repeat-separators.rs");
+    t.compile_fail("tests/18-empty-plus-repeat.rs");
+    t.compile_fail("tests/19-iteration-limit.rs");
+    t.compile_fail("tests/20-reversed-bounds.rs");
+    t.compile_fail("tests/21-unused-variable.rs");
+    t.compile_fail("tests/22-iteration-in-errors.rs");
+    t.pass("tests/23-conditional-blocks.rs");
+    t.compile_fail("tests/24-conditional-errors.rs");
+    t.pass("tests/25-attribute-macro.rs");
+    t.pass("tests/26-large-stepped-range.rs");
+    t.compile_fail("tests/27-format-spec-errors.rs");
 }
@@ -0,0 +1,265 @@
+// The orders #[sorted] can check, chosen with `#[sorted(order = "...")]`.
+//
+// The default compares names as strings, so `Http10` sorts before `Http2` and
+// every uppercase letter before any lowercase one. The other orders cover the
+// usual ways a human would expect such names to be sorted.
+//
+// This is an ordinary library rather than part of the proc-macro crate so that
+// the `sort` command of the workshop binary compares names, and splits lists
+// into sections, the same way.
+
+use proc_macro2::Span;
+use std::cmp::Ordering;
+use syn::meta::ParseNestedMeta;
+use syn::{Error, Expr, ExprLit, ExprUnary, Lit, LitStr, Pat, Result, UnOp};
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum SortOrder {
+    /// Plain string comparison
+    #[default]
+    Lexicographic,
+    /// Runs of digits compare as numbers: `Http2` before `Http10`
+    Natural,
+    /// Ignores case, falling back to the plain order for names that differ
+    /// only in case
+    CaseInsensitive,
+    /// Descending plain string comparison
+    Reverse,
+    /// Enum variants by their discriminant value
+    Discriminant,
+}
+
+/// A variant, field, item or match arm to be checked
+pub struct Entry {
+    pub name: String,
+    pub span: Span,
+    /// The discriminant, for enums checked in discriminant order, or the value
+    /// of a numeric literal or range pattern
+    pub value: Option<i128>,
+}
+
+impl SortOrder {
+    /// Parse one `order = "..."` argument
+    pub fn parse_meta(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if !meta.path.is_ident("order") {
+            return Err(meta.error("unsupported #[sorted] argument, expected `order = \"...\"`"));
+        }
+        let lit: LitStr = meta.value()?.parse()?;
+        *self = match lit.value().as_str() {
+            "lexicographic" => SortOrder::Lexicographic,
+            "natural" => SortOrder::Natural,
+            "case_insensitive" => SortOrder::CaseInsensitive,
+            "reverse" => SortOrder::Reverse,
+            "discriminant" => SortOrder::Discriminant,
+            other => {
+                return Err(Error::new(
+                    lit.span(),
+                    format!(
+                        "unknown sort order `{}`, expected one of \"lexicographic\", \
+                         \"natural\", \"case_insensitive\", \"reverse\" or \"discriminant\"",
+                        other
+                    ),
+                ));
+            }
+        };
+        Ok(())
+    }
+
+    pub fn compare(self, a: &Entry, b: &Entry) -> Ordering {
+        match self {
+            SortOrder::Discriminant => a.value.cmp(&b.value),
+            // Literal and range patterns compare as numbers in any order
+            _ if a.value.is_some() && b.value.is_some() => {
+                let ordering = a.value.cmp(&b.value);
+                if self == SortOrder::Reverse {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            }
+            SortOrder::Lexicographic => a.name.cmp(&b.name),
+            SortOrder::Natural => natural_cmp(&a.name, &b.name),
+            SortOrder::CaseInsensitive => a
+                .name
+                .to_lowercase()
+                .cmp(&b.name.to_lowercase())
+                .then_with(|| a.name.cmp(&b.name)),
+            SortOrder::Reverse => b.name.cmp(&a.name),
+        }
+    }
+
+    /// How the order is named in error messages, empty for the default
+    fn describe(self, entry: &Entry, before: &Entry) -> String {
+        match self {
+            SortOrder::Lexicographic => String::new(),
+            SortOrder::Natural => " in natural order".to_owned(),
+            SortOrder::CaseInsensitive => " ignoring case".to_owned(),
+            SortOrder::Reverse => " in reverse order".to_owned(),
+            SortOrder::Discriminant => match (entry.value, before.value) {
+                (Some(value), Some(before)) => {
+                    format!(" by discriminant ({} < {})", value, before)
+                }
+                _ => " by discriminant".to_owned(),
+            },
+        }
+    }
+
+    /// The names of all entries in this order, for the help note. The sort is
+    /// stable, so entries that compare equal keep their relative order.
+    pub fn sorted_names(self, entries: &[Entry]) -> String {
+        let mut sorted: Vec<&Entry> = entries.iter().collect();
+        sorted.sort_by(|a, b| self.compare(a, b));
+        sorted
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Every entry that comes after one it should sort before, with the error
+    /// message for it
+    pub fn violations(self, entries: &[Entry]) -> Vec<(&Entry, String)> {
+        let mut violations = Vec::new();
+        for (i, curr) in entries.iter().enumerate() {
+            let Some(should_before) = entries[..i]
+                .iter()
+                .find(|entry| self.compare(curr, entry) == Ordering::Less)
+            else {
+                continue;
+            };
+            let message = format!(
+                "{} should sort before {}{}",
+                curr.name,
+                should_before.name,
+                self.describe(curr, should_before)
+            );
+            violations.push((curr, message));
+        }
+        violations
+    }
+}
+
+/// The entries of a list marked #[sorted], split into sections that are each
+/// sorted on their own. An entry marked with `#[sorted::section]` starts a new
+/// section. In a match, so does every catch-all arm: the arms after it only get
+/// what it leaves over, so moving them ahead of it changes what they match.
+pub struct Sections<T> {
+    sections: Vec<Vec<T>>,
+    /// Whether an entry without a name ends the section
+    split_unnamed: bool,
+}
+
+impl<T> Sections<T> {
+    /// Sections of variants, fields or items
+    pub fn items() -> Self {
+        Sections {
+            sections: vec![Vec::new()],
+            split_unnamed: false,
+        }
+    }
+
+    /// Sections of match arms, where a catch-all has no name
+    pub fn arms() -> Self {
+        Sections {
+            sections: vec![Vec::new()],
+            split_unnamed: true,
+        }
+    }
+
+    /// Add an entry, or with `None` something that is not sorted, such as a
+    /// catch-all arm or a macro call among impl items
+    pub fn push(&mut self, marked: bool, entry: Option<T>) {
+        if marked {
+            self.sections.push(Vec::new());
+        }
+        match entry {
+            Some(entry) => self.sections.last_mut().unwrap().push(entry),
+            None if self.split_unnamed => self.sections.push(Vec::new()),
+            None => {}
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<Vec<T>> {
+        self.sections
+    }
+}
+
+/// Whether the pattern matches anything: `_`, or a plain binding. Bindings are
+/// told apart from unit variants brought into scope with `use` by being
+/// lowercase, as the naming conventions have it.
+pub fn is_catch_all(pat: &Pat) -> bool {
+    match pat {
+        Pat::Wild(_) => true,
+        Pat::Ident(ident) => {
+            ident.subpat.is_none()
+                && ident
+                    .ident
+                    .to_string()
+                    .starts_with(|c: char| c.is_lowercase() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+/// Compare names with runs of digits taken as numbers
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        match (a.is_empty(), b.is_empty()) {
+            (true, true) => return Ordering::Equal,
+            (true, false) => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            (false, false) => {}
+        }
+
+        let (chunk_a, rest_a) = split_chunk(a);
+        let (chunk_b, rest_b) = split_chunk(b);
+        let is_number = |chunk: &str| chunk.starts_with(|c: char| c.is_ascii_digit());
+        let ordering = if is_number(chunk_a) && is_number(chunk_b) {
+            // Longer numbers are larger once leading zeros are removed, and
+            // `01` sorts after `1`
+            let (trimmed_a, trimmed_b) = (
+                chunk_a.trim_start_matches('0'),
+                chunk_b.trim_start_matches('0'),
+            );
+            trimmed_a
+                .len()
+                .cmp(&trimmed_b.len())
+                .then_with(|| trimmed_a.cmp(trimmed_b))
+                .then_with(|| chunk_a.len().cmp(&chunk_b.len()))
+        } else {
+            chunk_a.cmp(chunk_b)
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+        (a, b) = (rest_a, rest_b);
+    }
+}
+
+/// Split off a leading run of digits or of non-digits
+fn split_chunk(s: &str) -> (&str, &str) {
+    let digits = s.starts_with(|c: char| c.is_ascii_digit());
+    let end = s
+        .find(|c: char| c.is_ascii_digit() != digits)
+        .unwrap_or(s.len());
+    s.split_at(end)
+}
+
+/// The value of an integer literal discriminant such as `3` or `-1`
+pub fn discriminant_value(expr: &Expr) -> Result<i128> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => lit.base10_parse(),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => Ok(-discriminant_value(expr)?),
+        _ => Err(Error::new_spanned(
+            expr,
+            "order = \"discriminant\" requires integer literal discriminants",
+        )),
+    }
+}
@@ -0,0 +1,142 @@
+// Code generated for enums with `#[sorted(names)]`, `#[sorted(from_name)]` or
+// `#[sorted(ord)]`.
+//
+// By the time this runs the variants are known to be in order, so they can be
+// listed as written and `VARIANTS` comes out sorted. `from_name` and `Ord` go
+// by the names compared as strings instead, whichever order is checked.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Error, Fields, Ident, ItemEnum, Result};
+
+use crate::SortedArgs;
+
+pub(crate) fn enum_helpers(item: &ItemEnum, args: &SortedArgs) -> Result<TokenStream> {
+    let mut methods = TokenStream::new();
+    if args.names || args.from_name {
+        methods.extend(names(item));
+    }
+    if args.from_name {
+        methods.extend(from_name(item)?);
+    }
+
+    let mut tokens = TokenStream::new();
+    if !methods.is_empty() {
+        let ident = &item.ident;
+        let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+        tokens.extend(quote! {
+            impl #impl_generics #ident #ty_generics #where_clause {
+                #methods
+            }
+        });
+    }
+    if args.ord {
+        tokens.extend(ord(item)?);
+    }
+    Ok(tokens)
+}
+
+/// `VARIANTS` and `name()`
+fn names(item: &ItemEnum) -> TokenStream {
+    let vis = &item.vis;
+    let idents = item.variants.iter().map(|variant| &variant.ident);
+    let names: Vec<String> = item
+        .variants
+        .iter()
+        .map(|variant| variant.ident.unraw().to_string())
+        .collect();
+
+    quote! {
+        /// The names of the variants, in sorted order
+        #vis const VARIANTS: &'static [&'static str] = &[#(#names),*];
+
+        /// The name of the variant
+        #vis fn name(&self) -> &'static str {
+            match *self {
+                #(Self::#idents { .. } => #names,)*
+            }
+        }
+    }
+}
+
+/// `from_name()`, a binary search over the names
+fn from_name(item: &ItemEnum) -> Result<TokenStream> {
+    require_unit_variants(item, "from_name")?;
+
+    let variants = by_name(item);
+    let names = variants.iter().map(|(name, _)| name);
+    let arms = variants.iter().enumerate().map(|(i, (_, ident))| {
+        quote!(::core::result::Result::Ok(#i) => ::core::option::Option::Some(Self::#ident))
+    });
+
+    let vis = &item.vis;
+    Ok(quote! {
+        /// The variant with the given name
+        #vis fn from_name(name: &str) -> ::core::option::Option<Self> {
+            const NAMES: &[&str] = &[#(#names),*];
+            match NAMES.binary_search(&name) {
+                #(#arms,)*
+                _ => ::core::option::Option::None,
+            }
+        }
+    })
+}
+
+/// `PartialOrd` and `Ord` by the variant names, as `name()` would compare
+fn ord(item: &ItemEnum) -> Result<TokenStream> {
+    require_unit_variants(item, "ord")?;
+
+    let ident = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+    let variants = by_name(item);
+    let idents = variants.iter().map(|(_, ident)| ident);
+    let positions = 0..variants.len();
+
+    Ok(quote! {
+        impl #impl_generics ::core::cmp::PartialOrd for #ident #ty_generics #where_clause {
+            fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                ::core::option::Option::Some(::core::cmp::Ord::cmp(self, other))
+            }
+        }
+
+        impl #impl_generics ::core::cmp::Ord for #ident #ty_generics #where_clause {
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                let position = |value: &Self| -> usize {
+                    match *value {
+                        #(Self::#idents => #positions,)*
+                    }
+                };
+                ::core::cmp::Ord::cmp(&position(self), &position(other))
+            }
+        }
+    })
+}
+
+/// The names and variants by string comparison of the names, whichever order
+/// the variants are checked in
+fn by_name(item: &ItemEnum) -> Vec<(String, &Ident)> {
+    let mut variants: Vec<_> = item
+        .variants
+        .iter()
+        .map(|variant| (variant.ident.unraw().to_string(), &variant.ident))
+        .collect();
+    variants.sort_by(|a, b| a.0.cmp(&b.0));
+    variants
+}
+
+/// `from_name()` has nothing but the name to build a value from, and `Ord`
+/// would call values equal that `Eq` tells apart by their fields
+fn require_unit_variants(item: &ItemEnum, option: &str) -> Result<()> {
+    match item
+        .variants
+        .iter()
+        .find(|variant| !matches!(variant.fields, Fields::Unit))
+    {
+        Some(variant) => Err(Error::new_spanned(
+            &variant.fields,
+            format!("#[sorted({})] requires variants without fields", option),
+        )),
+        None => Ok(()),
+    }
+}
@@ -1,91 +1,356 @@
 use proc_macro::TokenStream;
-use proc_macro2::Span;
-use proc_macro_error2::proc_macro_error;
-use quote::quote;
+use proc_macro2::{Group, Span, TokenStream as TokenStream2, TokenTree};
+use proc_macro_error2::{emit_error, proc_macro_error, set_dummy};
+use quote::{quote, quote_spanned, ToTokens};
+use std::mem;
 use syn::{
-    parse_macro_input, spanned::Spanned, visit_mut::VisitMut, Error, ExprMatch, Item, ItemFn, Pat,
-    Result,
+    parse_macro_input, parse_quote, spanned::Spanned, visit_mut::VisitMut, Arm, Attribute, Error,
+    Expr, ExprMatch, ExprStruct, Fields, Ident, ImplItem, Item, ItemEnum, Lit, LitStr, Local,
+    Member, Meta, Pat, PatStruct, Path, Result, TraitItem, UseTree,
 };
 
+use sorted_order::{discriminant_value, is_catch_all, Entry, Sections, SortOrder};
+
+mod helpers;
+
 #[proc_macro_attribute]
 #[proc_macro_error]
 pub fn sorted(args: TokenStream, input: TokenStream) -> TokenStream {
-    let _ = args;
-    let item = parse_macro_input!(input as Item);
+    let mut item = parse_macro_input!(input as Item);
 
-    match sorted_impl(&item) {
-        Ok(tokens) => tokens.into(),
+    let result = parse_sorted_args(args.into()).and_then(|args| sorted_impl(&mut item, &args));
+    let tokens = match result {
+        Ok(tokens) => tokens,
         Err(e) => {
-            // Return both the error and the original item so compilation can continue
+            // Return both the error and the original item so compilation can
+            // continue, without markers the compiler would then complain about
+            strip_section_markers(&mut item);
             let item_tokens = quote! { #item };
             let error_tokens = e.to_compile_error();
             quote! {
                 #error_tokens
                 #item_tokens
             }
-            .into()
         }
-    }
+    };
+    keep_output(tokens)
 }
 
-fn sorted_impl(item: &Item) -> Result<proc_macro2::TokenStream> {
-    let Item::Enum(item_enum) = item else {
-        return Err(Error::new_spanned(
-            quote! { #[sorted] },
-            "expected enum or match expression",
-        ));
+/// Ordering errors are emitted rather than returned, so that they can carry a
+/// help note, and once any is emitted the output is replaced by the dummy
+fn keep_output(tokens: TokenStream2) -> TokenStream {
+    set_dummy(tokens.clone());
+    tokens.into()
+}
+
+/// The arguments of `#[sorted(...)]` on an item
+#[derive(Default)]
+struct SortedArgs {
+    order: SortOrder,
+    /// Generate `VARIANTS` and `name()`
+    names: bool,
+    /// Generate `from_name()`, and with it `VARIANTS` and `name()`
+    from_name: bool,
+    /// Implement `PartialOrd` and `Ord` by variant name
+    ord: bool,
+}
+
+fn parse_sorted_args(args: TokenStream2) -> Result<SortedArgs> {
+    let mut sorted_args = SortedArgs::default();
+    let parser = syn::meta::parser(|meta| {
+        let flag = if meta.path.is_ident("names") {
+            &mut sorted_args.names
+        } else if meta.path.is_ident("from_name") {
+            &mut sorted_args.from_name
+        } else if meta.path.is_ident("ord") {
+            &mut sorted_args.ord
+        } else if meta.path.is_ident("order") {
+            return sorted_args.order.parse_meta(meta);
+        } else {
+            return Err(meta.error(
+                "unsupported #[sorted] argument, expected `order = \"...\"`, `names`, \
+                 `from_name` or `ord`",
+            ));
+        };
+        *flag = true;
+        Ok(())
+    });
+    syn::parse::Parser::parse2(parser, args)?;
+    Ok(sorted_args)
+}
+
+fn sorted_impl(item: &mut Item, args: &SortedArgs) -> Result<TokenStream2> {
+    let order = args.order;
+    if !matches!(item, Item::Enum(_)) {
+        let only_enums = if order == SortOrder::Discriminant {
+            Some("order = \"discriminant\" is only supported on enums")
+        } else if args.names || args.from_name || args.ord {
+            Some("names, from_name and ord are only supported on enums")
+        } else {
+            None
+        };
+        if let Some(message) = only_enums {
+            return Err(Error::new(Span::call_site(), message));
+        }
+    }
+
+    let mut sections = Sections::items();
+    match item {
+        Item::Enum(item_enum) => enum_entries(item_enum, order, &mut sections)?,
+        Item::Struct(item_struct) => field_entries(&mut item_struct.fields, &mut sections)?,
+        Item::Impl(item_impl) => {
+            for item in &mut item_impl.items {
+                let (attrs, ident) = match item {
+                    ImplItem::Const(item) => (&mut item.attrs, Some(&item.ident)),
+                    ImplItem::Fn(item) => (&mut item.attrs, Some(&item.sig.ident)),
+                    ImplItem::Type(item) => (&mut item.attrs, Some(&item.ident)),
+                    ImplItem::Macro(item) => (&mut item.attrs, None),
+                    _ => continue,
+                };
+                push_entry(&mut sections, attrs, ident.map(ident_entry));
+            }
+        }
+        Item::Trait(item_trait) => {
+            for item in &mut item_trait.items {
+                let (attrs, ident) = match item {
+                    TraitItem::Const(item) => (&mut item.attrs, Some(&item.ident)),
+                    TraitItem::Fn(item) => (&mut item.attrs, Some(&item.sig.ident)),
+                    TraitItem::Type(item) => (&mut item.attrs, Some(&item.ident)),
+                    TraitItem::Macro(item) => (&mut item.attrs, None),
+                    _ => continue,
+                };
+                push_entry(&mut sections, attrs, ident.map(ident_entry));
+            }
+        }
+        Item::Use(item_use) => check_use_tree(&item_use.tree, order)?,
+        _ => {
+            return Err(Error::new_spanned(
+                quote! { #[sorted] },
+                "expected enum, struct, impl, trait, use or match expression",
+            ));
+        }
+    }
+    check_sections(order, sections);
+
+    let helpers = match item {
+        Item::Enum(item_enum) => helpers::enum_helpers(item_enum, args)?,
+        _ => TokenStream2::new(),
     };
+    Ok(quote! {
+        #item
+        #helpers
+    })
+}
 
-    // Check that variants are sorted
-    let variants: Vec<_> = item_enum.variants.iter().collect();
+fn enum_entries(
+    item_enum: &mut ItemEnum,
+    order: SortOrder,
+    sections: &mut Sections<Entry>,
+) -> Result<()> {
+    let mut next_discriminant = 0;
+    for variant in &mut item_enum.variants {
+        // Implicit discriminants count up from the previous one
+        let mut discriminant = None;
+        if order == SortOrder::Discriminant {
+            let value = match &variant.discriminant {
+                Some((_, expr)) => discriminant_value(expr)?,
+                None => next_discriminant,
+            };
+            next_discriminant = value + 1;
+            discriminant = Some(value);
+        }
+        let entry = Entry {
+            name: variant.ident.to_string(),
+            span: variant.ident.span(),
+            value: discriminant,
+        };
+        push_entry(sections, &mut variant.attrs, Some(entry));
+    }
+    Ok(())
+}
 
-    for i in 1..variants.len() {
-        let prev_name = &variants[i - 1].ident;
-        let curr_name = &variants[i].ident;
+fn field_entries(fields: &mut Fields, sections: &mut Sections<Entry>) -> Result<()> {
+    match fields {
+        Fields::Named(fields) => {
+            for field in &mut fields.named {
+                let entry = field.ident.as_ref().map(ident_entry);
+                push_entry(sections, &mut field.attrs, entry);
+            }
+            Ok(())
+        }
+        Fields::Unnamed(fields) => Err(Error::new_spanned(
+            fields,
+            "#[sorted] requires named fields",
+        )),
+        Fields::Unit => Ok(()),
+    }
+}
+
+/// Add an entry to the sections, taking note of any `#[sorted::section]`
+/// marker on it. The marker is removed, since there is no such attribute for
+/// the compiler to find.
+fn push_entry(sections: &mut Sections<Entry>, attrs: &mut Vec<Attribute>, entry: Option<Entry>) {
+    let count = attrs.len();
+    attrs.retain(|attr| !is_section_marker(attr));
+    sections.push(attrs.len() != count, entry);
+}
 
-        if curr_name >= prev_name {
-            continue;
+/// Remove the `#[sorted::section]` markers from an item that was not checked
+fn strip_section_markers(item: &mut Item) {
+    let strip = |attrs: &mut Vec<Attribute>| attrs.retain(|attr| !is_section_marker(attr));
+    match item {
+        Item::Enum(item_enum) => {
+            for variant in &mut item_enum.variants {
+                strip(&mut variant.attrs);
+            }
         }
+        Item::Struct(item_struct) => {
+            for field in &mut item_struct.fields {
+                strip(&mut field.attrs);
+            }
+        }
+        Item::Impl(item_impl) => {
+            for item in &mut item_impl.items {
+                match item {
+                    ImplItem::Const(item) => strip(&mut item.attrs),
+                    ImplItem::Fn(item) => strip(&mut item.attrs),
+                    ImplItem::Type(item) => strip(&mut item.attrs),
+                    ImplItem::Macro(item) => strip(&mut item.attrs),
+                    _ => {}
+                }
+            }
+        }
+        Item::Trait(item_trait) => {
+            for item in &mut item_trait.items {
+                match item {
+                    TraitItem::Const(item) => strip(&mut item.attrs),
+                    TraitItem::Fn(item) => strip(&mut item.attrs),
+                    TraitItem::Type(item) => strip(&mut item.attrs),
+                    TraitItem::Macro(item) => strip(&mut item.attrs),
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
 
-        // Find what this should sort before
-        let should_before = variants[..i]
-            .iter()
-            .find(|v| curr_name < &v.ident)
-            .map(|v| &v.ident)
-            .unwrap_or(prev_name);
+fn is_section_marker(attr: &Attribute) -> bool {
+    let segments: Vec<String> = attr
+        .path()
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    segments == ["sorted", "section"]
+}
 
-        return Err(Error::new(
-            curr_name.span(),
-            format!("{} should sort before {}", curr_name, should_before),
-        ));
+/// Check each section on its own, there is no order between sections
+fn check_sections(order: SortOrder, sections: Sections<Entry>) {
+    for entries in sections.into_vec() {
+        check_order(order, &entries);
     }
+}
 
-    Ok(quote! { #item })
+/// Report every entry that comes after one it should sort before. The first
+/// error also carries the sorted order as a help note.
+fn check_order(order: SortOrder, entries: &[Entry]) {
+    for (i, (entry, message)) in order.violations(entries).into_iter().enumerate() {
+        if i == 0 {
+            emit_error!(
+                entry.span, "{}", message;
+                help = "the sorted order is {}", order.sorted_names(entries)
+            );
+        } else {
+            emit_error!(entry.span, "{}", message);
+        }
+    }
+}
+
+fn ident_entry(ident: &Ident) -> Entry {
+    Entry {
+        name: ident.to_string(),
+        span: ident.span(),
+        value: None,
+    }
+}
+
+/// Check every `{...}` list in a use tree. A leading `self` is allowed, as
+/// rustfmt puts it there.
+fn check_use_tree(tree: &UseTree, order: SortOrder) -> Result<()> {
+    match tree {
+        UseTree::Path(path) => check_use_tree(&path.tree, order),
+        UseTree::Group(group) => {
+            let mut entries = Vec::new();
+            let mut error = None;
+            for (i, tree) in group.items.iter().enumerate() {
+                combine(&mut error, check_use_tree(tree, order));
+                let ident = match tree {
+                    UseTree::Path(path) => &path.ident,
+                    UseTree::Name(name) => &name.ident,
+                    UseTree::Rename(rename) => &rename.ident,
+                    UseTree::Glob(glob) => {
+                        entries.push(Entry {
+                            name: "*".to_owned(),
+                            span: glob.star_token.span,
+                            value: None,
+                        });
+                        continue;
+                    }
+                    UseTree::Group(_) => continue,
+                };
+                if ident == "self" {
+                    if i > 0 {
+                        let e = Error::new(ident.span(), "self should sort first");
+                        combine(&mut error, Err(e));
+                    }
+                    continue;
+                }
+                entries.push(ident_entry(ident));
+            }
+            check_order(order, &entries);
+            error.map_or(Ok(()), Err)
+        }
+        UseTree::Name(_) | UseTree::Rename(_) | UseTree::Glob(_) => Ok(()),
+    }
 }
 
 #[proc_macro_attribute]
 #[proc_macro_error]
 pub fn check(args: TokenStream, input: TokenStream) -> TokenStream {
     let _ = args;
-    let mut item_fn = parse_macro_input!(input as ItemFn);
+    let mut item = parse_macro_input!(input as Item);
 
-    match check_impl(&mut item_fn) {
-        Ok(()) => quote! { #item_fn }.into(),
+    let tokens = match check_impl(&mut item) {
+        Ok(()) => quote! { #item },
         Err(e) => {
-            let item_tokens = quote! { #item_fn };
+            let item_tokens = quote! { #item };
             let error_tokens = e.to_compile_error();
             quote! {
                 #error_tokens
                 #item_tokens
             }
-            .into()
         }
-    }
+    };
+    keep_output(tokens)
 }
 
-fn check_impl(item_fn: &mut ItemFn) -> Result<()> {
+/// Check every `#[sorted]` in a function, or in all the functions of an impl
+/// block, trait or inline module, closures and async blocks included
+fn check_impl(item: &mut Item) -> Result<()> {
     let mut visitor = SortedChecker { error: None };
-    visitor.visit_item_fn_mut(item_fn);
+    match item {
+        Item::Fn(item_fn) => visitor.visit_item_fn_mut(item_fn),
+        Item::Impl(item_impl) => visitor.visit_item_impl_mut(item_impl),
+        Item::Trait(item_trait) => visitor.visit_item_trait_mut(item_trait),
+        Item::Mod(item_mod) if item_mod.content.is_some() => visitor.visit_item_mod_mut(item_mod),
+        _ => {
+            return Err(Error::new(
+                Span::call_site(),
+                "expected fn, impl, trait or inline mod",
+            ));
+        }
+    }
     visitor.error.map_or(Ok(()), Err)
 }
 
@@ -93,89 +358,367 @@ struct SortedChecker {
     error: Option<Error>,
 }
 
-impl VisitMut for SortedChecker {
-    fn visit_expr_match_mut(&mut self, expr: &mut ExprMatch) {
-        // Check if this match has a #[sorted] attribute
-        let Some(idx) = expr
-            .attrs
-            .iter()
-            .position(|attr| attr.path().is_ident("sorted"))
-        else {
-            syn::visit_mut::visit_expr_match_mut(self, expr);
-            return;
-        };
+/// Add the error from `result`, if any, to those already found
+fn combine(error: &mut Option<Error>, result: Result<()>) {
+    match (error, result) {
+        (_, Ok(())) => {}
+        (Some(error), Err(e)) => error.combine(e),
+        (error, Err(e)) => *error = Some(e),
+    }
+}
 
-        // Remove the #[sorted] attribute
-        expr.attrs.remove(idx);
+/// Remove and return the `#[sorted]` attribute, if there is one
+fn take_sorted(attrs: &mut Vec<Attribute>) -> Option<Attribute> {
+    let idx = attrs
+        .iter()
+        .position(|attr| attr.path().is_ident("sorted"))?;
+    Some(attrs.remove(idx))
+}
 
-        // Check that the match arms are sorted (only capture first error)
-        if self.error.is_none() {
-            if let Err(e) = check_match_arms_sorted(&expr.arms) {
-                self.error = Some(e);
+impl SortedChecker {
+    /// Check a match marked with `attr`. With `exhaustive_of`, this also
+    /// returns a check to compile next to the match.
+    fn check_match(&mut self, attr: &Attribute, expr: &mut ExprMatch) -> Option<TokenStream2> {
+        let args = match check_args(attr) {
+            Ok(args) => args,
+            Err(e) => {
+                combine(&mut self.error, Err(e));
+                return None;
+            }
+        };
+        combine(
+            &mut self.error,
+            check_match_arms_sorted(&mut expr.arms, args.order),
+        );
+        let (enum_path, span) = args.exhaustive_of?;
+        Some(exhaustiveness_check(&expr.arms, &enum_path, span))
+    }
+}
+
+impl VisitMut for SortedChecker {
+    // Matches are handled from here rather than from visit_expr_match_mut, so
+    // that the expression can be wrapped together with its exhaustiveness check
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        let mut check = None;
+        if let Expr::Match(expr) = expr {
+            if let Some(attr) = take_sorted(&mut expr.attrs) {
+                check = self.check_match(&attr, expr);
             }
         }
 
         // Continue visiting nested expressions
-        syn::visit_mut::visit_expr_match_mut(self, expr);
+        syn::visit_mut::visit_expr_mut(self, expr);
+
+        if let Some(check) = check {
+            prepend(expr, check);
+        }
     }
-}
 
-fn check_match_arms_sorted(arms: &[syn::Arm]) -> Result<()> {
-    // Extract arm names, handling patterns
-    let mut arm_names: Vec<(String, Span)> = Vec::new();
+    fn visit_expr_struct_mut(&mut self, expr: &mut ExprStruct) {
+        if let Some(attr) = take_sorted(&mut expr.attrs) {
+            let result = match_order(&attr).and_then(|order| {
+                let members = expr.fields.iter().map(|field| &field.member);
+                check_members(members, order)
+            });
+            combine(&mut self.error, result);
+        }
 
-    for arm in arms {
-        match get_pattern_name(&arm.pat) {
-            PatternKind::Name(name, span) => arm_names.push((name, span)),
-            PatternKind::Wild => {} // Wildcard should be last, skip
-            PatternKind::Unsupported(span) => {
-                return Err(Error::new(span, "unsupported by #[sorted]"));
+        syn::visit_mut::visit_expr_struct_mut(self, expr);
+    }
+
+    // Patterns cannot carry attributes, so struct patterns are checked through
+    // the `let` or match arm they appear in
+    fn visit_local_mut(&mut self, local: &mut Local) {
+        if let Some(attr) = take_sorted(&mut local.attrs) {
+            let mut check = None;
+            let init = local.init.as_mut().map(|init| &mut *init.expr);
+            let result = match (&local.pat, init) {
+                (Pat::Struct(pat), _) => {
+                    match_order(&attr).and_then(|order| check_pat_fields_sorted(pat, order))
+                }
+                (_, Some(Expr::Struct(expr))) => match_order(&attr).and_then(|order| {
+                    let members = expr.fields.iter().map(|field| &field.member);
+                    check_members(members, order)
+                }),
+                (_, Some(Expr::Match(expr))) => {
+                    check = self.check_match(&attr, expr);
+                    Ok(())
+                }
+                _ => Err(Error::new_spanned(
+                    &local.pat,
+                    "expected a struct pattern, struct expression or match expression",
+                )),
+            };
+            combine(&mut self.error, result);
+            if let (Some(check), Some(init)) = (check, &mut local.init) {
+                prepend(&mut init.expr, check);
             }
         }
-    }
 
-    // Check sorting
-    for i in 1..arm_names.len() {
-        let (prev_name, _) = &arm_names[i - 1];
-        let (curr_name, curr_span) = &arm_names[i];
+        syn::visit_mut::visit_local_mut(self, local);
+    }
 
-        if curr_name >= prev_name {
-            continue;
+    fn visit_arm_mut(&mut self, arm: &mut Arm) {
+        if let Some(attr) = take_sorted(&mut arm.attrs) {
+            let result = match_order(&attr).and_then(|order| match &arm.pat {
+                Pat::Struct(pat) => check_pat_fields_sorted(pat, order),
+                pat => Err(Error::new_spanned(pat, "expected a struct pattern")),
+            });
+            combine(&mut self.error, result);
         }
 
-        // Find what this should sort before
-        let should_before = arm_names[..i]
-            .iter()
-            .find(|(n, _)| curr_name < n)
-            .map(|(n, _)| n.as_str())
-            .unwrap_or(prev_name.as_str());
+        syn::visit_mut::visit_arm_mut(self, arm);
+    }
+}
+
+/// Put `check` in a block ahead of the expression
+fn prepend(expr: &mut Expr, check: TokenStream2) {
+    let inner = mem::replace(expr, Expr::Verbatim(TokenStream2::new()));
+    *expr = parse_quote!({
+        #check
+        #inner
+    });
+}
+
+/// A match on `enum_path` with the unguarded arms but no catch-all. The
+/// compiler rejects it unless the arms name every variant, and reports the
+/// ones that are missing.
+///
+/// Only the value and the enum path carry the span of the `exhaustive_of`
+/// string, for the error to point there. The compiler would also suggest the
+/// missing arms after the last arm, which is not where the user would write
+/// them, but it leaves that out when the last two arms come from different
+/// expansions. Two guarded arms, which never count towards exhaustiveness,
+/// make sure of that.
+fn exhaustiveness_check(arms: &[Arm], enum_path: &Path, span: Span) -> TokenStream2 {
+    let pats = arms
+        .iter()
+        .filter(|arm| arm.guard.is_none() && !is_catch_all(&arm.pat))
+        .map(|arm| match &arm.pat {
+            // The check matches on a value, where the arms may match on a
+            // reference
+            Pat::Reference(reference) => &*reference.pat,
+            pat => pat,
+        })
+        .map(|pat| respan(pat.to_token_stream(), Span::call_site()));
+    let value = Ident::new("__sorted_value", span);
+    let last = quote_spanned!(Span::mixed_site()=> _ if false => {});
+    quote! {
+        #[allow(unused_variables, unreachable_patterns)]
+        let _ = |#value: #enum_path| match #value {
+            #(#pats => {})*
+            _ if false => {}
+            #last
+        };
+    }
+}
+
+/// The tokens with every span, also those inside groups, set to `span`
+fn respan(tokens: TokenStream2, span: Span) -> TokenStream2 {
+    tokens
+        .into_iter()
+        .map(|mut tt| {
+            if let TokenTree::Group(group) = &tt {
+                let mut new_group = Group::new(group.delimiter(), respan(group.stream(), span));
+                new_group.set_span(span);
+                tt = TokenTree::Group(new_group);
+            }
+            tt.set_span(span);
+            tt
+        })
+        .collect()
+}
+
+fn check_pat_fields_sorted(pat: &PatStruct, order: SortOrder) -> Result<()> {
+    let members = pat.fields.iter().map(|field| &field.member);
+    check_members(members, order)
+}
 
+fn check_members<'a>(members: impl Iterator<Item = &'a Member>, order: SortOrder) -> Result<()> {
+    check_order(order, &member_entries(members)?);
+    Ok(())
+}
+
+fn member_entries<'a>(members: impl Iterator<Item = &'a Member>) -> Result<Vec<Entry>> {
+    members
+        .map(|member| match member {
+            Member::Named(ident) => Ok(ident_entry(ident)),
+            Member::Unnamed(_) => Err(Error::new_spanned(
+                member,
+                "#[sorted] requires named fields",
+            )),
+        })
+        .collect()
+}
+
+/// The arguments of a `#[sorted]` inside a function
+#[derive(Default)]
+struct CheckArgs {
+    order: SortOrder,
+    /// The enum named by `exhaustive_of = "..."`, and where it was named
+    exhaustive_of: Option<(Path, Span)>,
+}
+
+fn check_args(attr: &Attribute) -> Result<CheckArgs> {
+    let mut args = CheckArgs::default();
+    if let Meta::List(_) = attr.meta {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("exhaustive_of") {
+                let lit: LitStr = meta.value()?.parse()?;
+                args.exhaustive_of = Some((lit.parse()?, lit.span()));
+                Ok(())
+            } else {
+                args.order.parse_meta(meta)
+            }
+        })?;
+    }
+    if args.order == SortOrder::Discriminant {
+        return Err(Error::new_spanned(
+            attr,
+            "order = \"discriminant\" is only supported on enums",
+        ));
+    }
+    Ok(args)
+}
+
+/// The order requested by `#[sorted]` or `#[sorted(order = "...")]` on
+/// anything other than a match
+fn match_order(attr: &Attribute) -> Result<SortOrder> {
+    let args = check_args(attr)?;
+    if let Some((_, span)) = args.exhaustive_of {
         return Err(Error::new(
-            *curr_span,
-            format!("{} should sort before {}", curr_name, should_before),
+            span,
+            "exhaustive_of is only supported on match expressions",
         ));
     }
+    Ok(args.order)
+}
 
-    Ok(())
+/// Check the arms by their patterns. Guards are ignored, so arms with the same
+/// pattern and different guards may come in any order. The alternatives of an
+/// or-pattern must be sorted among themselves, and the first one places the
+/// arm. A catch-all without a guard has to be the last arm, and one with a
+/// guard ends a section, as moving arms across it would change what they match.
+fn check_match_arms_sorted(arms: &mut [Arm], order: SortOrder) -> Result<()> {
+    let mut sections = Sections::arms();
+    let mut error = None;
+    let mut unsupported = false;
+
+    let last = arms.len().saturating_sub(1);
+    for (i, arm) in arms.iter_mut().enumerate() {
+        if i != last && arm.guard.is_none() && is_catch_all(&arm.pat) {
+            let e = Error::new_spanned(
+                &arm.pat,
+                format!(
+                    "catch-all pattern `{}` should be the last arm",
+                    arm.pat.to_token_stream()
+                ),
+            );
+            combine(&mut error, Err(e));
+        }
+
+        let kind = match &arm.pat {
+            // Only strip any section marker once an arm was rejected, the
+            // order of the rest can't be judged anyway
+            _ if unsupported => PatternKind::Wild,
+            Pat::Or(pat) => {
+                let mut alternatives = Vec::new();
+                let mut kind = None;
+                for case in &pat.cases {
+                    match pattern_kind(case) {
+                        PatternKind::Entry(entry) => alternatives.push(entry),
+                        PatternKind::Wild => {}
+                        PatternKind::Unsupported(span) => {
+                            kind = Some(PatternKind::Unsupported(span));
+                            break;
+                        }
+                    }
+                }
+                check_order(order, &alternatives);
+                kind.unwrap_or_else(|| match alternatives.into_iter().next() {
+                    Some(entry) => PatternKind::Entry(entry),
+                    None => PatternKind::Wild,
+                })
+            }
+            pat => pattern_kind(pat),
+        };
+        let entry = match kind {
+            PatternKind::Entry(entry) => Some(entry),
+            PatternKind::Wild => None, // Catch-alls are not sorted, skip
+            PatternKind::Unsupported(span) => {
+                let e = Error::new(span, "unsupported by #[sorted]");
+                combine(&mut error, Err(e));
+                unsupported = true;
+                None
+            }
+        };
+        push_entry(&mut sections, &mut arm.attrs, entry);
+    }
+
+    if !unsupported {
+        check_sections(order, sections);
+    }
+    error.map_or(Ok(()), Err)
 }
 
 enum PatternKind {
-    Name(String, Span),
+    Entry(Entry),
     Wild,
     Unsupported(Span),
 }
 
-fn get_pattern_name(pat: &Pat) -> PatternKind {
+fn pattern_kind(pat: &Pat) -> PatternKind {
+    let named = |name, span| {
+        PatternKind::Entry(Entry {
+            name,
+            span,
+            value: None,
+        })
+    };
     match pat {
-        Pat::Ident(ident) => PatternKind::Name(ident.ident.to_string(), ident.ident.span()),
-        Pat::Path(path) => PatternKind::Name(path_to_string(&path.path), path_span(&path.path)),
-        Pat::TupleStruct(ts) => PatternKind::Name(path_to_string(&ts.path), path_span(&ts.path)),
-        Pat::Struct(ps) => PatternKind::Name(path_to_string(&ps.path), path_span(&ps.path)),
-        Pat::Wild(_) => PatternKind::Wild,
+        _ if is_catch_all(pat) => PatternKind::Wild,
+        Pat::Ident(ident) => match &ident.subpat {
+            Some((_, subpat)) => pattern_kind(subpat),
+            None => named(ident.ident.to_string(), ident.ident.span()),
+        },
+        Pat::Path(path) => named(path_to_string(&path.path), path_span(&path.path)),
+        Pat::TupleStruct(ts) => named(path_to_string(&ts.path), path_span(&ts.path)),
+        Pat::Struct(ps) => named(path_to_string(&ps.path), path_span(&ps.path)),
+        Pat::Reference(reference) => pattern_kind(&reference.pat),
+        Pat::Paren(paren) => pattern_kind(&paren.pat),
+        // Numbers, chars and bytes sort by value, strings by their text
+        Pat::Lit(lit) => PatternKind::Entry(Entry {
+            name: lit.lit.to_token_stream().to_string(),
+            span: lit.span(),
+            value: literal_value(&lit.lit),
+        }),
+        // Ranges sort by where they start, or end if open at the start
+        Pat::Range(range) => {
+            let bound = range.start.as_ref().or(range.end.as_ref());
+            let value = bound.and_then(|bound| match &**bound {
+                Expr::Lit(lit) => literal_value(&lit.lit),
+                _ => None,
+            });
+            PatternKind::Entry(Entry {
+                name: range.to_token_stream().to_string(),
+                span: range.span(),
+                value,
+            })
+        }
         _ => PatternKind::Unsupported(pat.span()),
     }
 }
 
+/// The numeric value of an integer, char or byte literal
+fn literal_value(lit: &Lit) -> Option<i128> {
+    match lit {
+        Lit::Int(lit) => lit.base10_parse().ok(),
+        Lit::Char(lit) => Some(u32::from(lit.value()).into()),
+        Lit::Byte(lit) => Some(lit.value().into()),
+        _ => None,
+    }
+}
+
 /// Get a span covering the path's last segment
 fn path_span(path: &syn::Path) -> Span {
     path.segments
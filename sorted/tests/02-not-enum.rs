@@ -1,14 +1,15 @@
-// The #[sorted] macro is only defined to work on enum types, so this is a test
-// to ensure that when it's attached to a struct (or anything else) it produces
-// some reasonable error. Your macro will need to look into the syn::Item that
-// it parsed to ensure that it represents an enum, returning an error for any
-// other type of Item such as a struct.
+// The #[sorted] macro is only defined to work on items with named entries to
+// sort (enums, structs, impl blocks, traits and use lists), so this is a test
+// to ensure that when it's attached to anything else, like a function, it
+// produces some reasonable error. Your macro will need to look into the
+// syn::Item that it parsed to ensure that it represents one of those, returning
+// an error for any other type of Item such as a function.
 //
 // This is an exercise in exploring how to return errors from procedural macros.
 // The goal is to produce an understandable error message which is tailored to
-// this specific macro (saying that #[sorted] cannot be applied to things other
-// than enum). For this you'll want to look at the syn::Error type, how to
-// construct it, and how to return it.
+// this specific macro (saying which items #[sorted] can be applied to). For
+// this you'll want to look at the syn::Error type, how to construct it, and how
+// to return it.
 //
 // Notice that the return value of an attribute macro is simply a TokenStream,
 // not a Result with an error. The syn::Error type provides a method to render
@@ -29,9 +30,8 @@
 use sorted::sorted;
 
 #[sorted]
-pub struct Error {
-    kind: ErrorKind,
-    message: String,
+pub fn error_kinds() -> [ErrorKind; 3] {
+    [ErrorKind::Io, ErrorKind::Syntax, ErrorKind::Eof]
 }
 
 enum ErrorKind {
@@ -0,0 +1,79 @@
+// By default names are compared as strings, which puts `Http10` before `Http2`
+// and every uppercase letter before any lowercase one. A different order can
+// be asked for, both on enums and on match expressions:
+//
+//     #[sorted(order = "natural")]            // Http2, Http10, Http11
+//     #[sorted(order = "case_insensitive")]   // alpha, Beta, gamma
+//     #[sorted(order = "reverse")]            // Zebra, Yak, Ant
+//     #[sorted(order = "discriminant")]       // by the value of each variant
+//
+// Discriminant order only makes sense for an enum definition. Variants
+// without an explicit discriminant take the previous one plus one, as usual.
+
+use sorted::sorted;
+
+#[sorted(order = "natural")]
+pub enum Protocol {
+    Http1,
+    Http2,
+    Http10,
+    Http11,
+    Tls1v2,
+    Tls1v10,
+}
+
+#[sorted(order = "case_insensitive")]
+#[allow(non_camel_case_types)]
+pub enum Mixed {
+    alpha,
+    Beta,
+    gamma,
+}
+
+#[sorted(order = "reverse")]
+pub enum Animal {
+    Zebra,
+    Yak,
+    Ant,
+}
+
+#[sorted(order = "discriminant")]
+#[repr(i8)]
+pub enum Status {
+    Failed = -1,
+    Ok,
+    Retry = 5,
+    Unknown = 10,
+    Busy,
+}
+
+#[sorted::check]
+fn version(protocol: &Protocol) -> u32 {
+    #[sorted(order = "natural")]
+    match protocol {
+        Protocol::Http1 => 1,
+        Protocol::Http2 => 2,
+        Protocol::Http10 => 10,
+        Protocol::Http11 => 11,
+        _ => 0,
+    }
+}
+
+#[sorted::check]
+fn is_a(animal: &Animal) -> bool {
+    #[sorted(order = "reverse")]
+    match animal {
+        Animal::Zebra => false,
+        Animal::Yak => false,
+        Animal::Ant => true,
+    }
+}
+
+fn main() {
+    assert_eq!(version(&Protocol::Http10), 10);
+    assert!(is_a(&Animal::Ant));
+    assert_eq!(Status::Busy as i8, 11);
+    let _ = (Mixed::alpha, Mixed::Beta, Mixed::gamma);
+    let _ = (Protocol::Tls1v2, Protocol::Tls1v10);
+    let _ = (Status::Failed, Status::Ok, Status::Retry, Status::Unknown);
+}
@@ -0,0 +1,38 @@
+// Each order reports variants out of place in its own terms, and an unknown
+// order or a discriminant order on a match expression is an error.
+
+use sorted::sorted;
+
+#[sorted(order = "natural")]
+pub enum Protocol {
+    Http10,
+    Http2,
+}
+
+#[sorted(order = "case_insensitive")]
+pub enum Mixed {
+    Beta,
+    Alpha,
+}
+
+#[sorted(order = "discriminant")]
+pub enum Status {
+    Ok = 1,
+    Failed = 0,
+}
+
+#[sorted(order = "alphabetical")]
+pub enum Unknown {
+    A,
+}
+
+#[sorted::check]
+fn version(protocol: &Protocol) -> u32 {
+    #[sorted(order = "discriminant")]
+    match protocol {
+        Protocol::Http10 => 10,
+        Protocol::Http2 => 2,
+    }
+}
+
+fn main() {}
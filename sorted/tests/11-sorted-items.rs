@@ -0,0 +1,85 @@
+// Beyond enums, #[sorted] checks the order of struct fields, the items of impl
+// blocks and traits, and the names in `use` lists. Inside a #[sorted::check]
+// function it also checks the fields of struct expressions, and of struct
+// patterns through the `let` or match arm they appear in.
+
+use sorted::sorted;
+
+#[sorted]
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display},
+    io,
+};
+
+#[sorted]
+pub struct Config {
+    name: String,
+    retries: u32,
+    timeout: u64,
+}
+
+#[sorted]
+impl Config {
+    const DEFAULT_RETRIES: u32 = 3;
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+}
+
+#[sorted]
+pub trait Describe {
+    type Output;
+
+    fn describe(&self) -> Self::Output;
+    fn summary(&self) -> String;
+}
+
+#[sorted(order = "case_insensitive")]
+#[allow(non_snake_case)]
+pub struct Point {
+    x: i32,
+    Y: i32,
+}
+
+impl Display for Config {
+    #[sorted::check]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        #[sorted]
+        let Config {
+            name,
+            retries,
+            timeout,
+        } = self;
+        write!(f, "{} {} {}", name, retries, timeout)
+    }
+}
+
+#[sorted::check]
+fn build(map: BTreeMap<String, u64>, _: HashMap<u8, u8>) -> io::Result<Config> {
+    let config = #[sorted]
+    Config {
+        name: "default".to_owned(),
+        retries: Config::DEFAULT_RETRIES,
+        timeout: map.get("timeout").copied().unwrap_or(30),
+    };
+
+    match config {
+        #[sorted]
+        Config { name, retries, .. } if name.is_empty() => Ok(Config {
+            name: "unnamed".to_owned(),
+            retries,
+            timeout: 0,
+        }),
+        config => Ok(config),
+    }
+}
+
+fn main() {
+    let _ = build(BTreeMap::new(), HashMap::new());
+}
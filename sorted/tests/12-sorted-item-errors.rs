@@ -0,0 +1,44 @@
+// Struct fields, impl and trait items, use lists and struct expressions are
+// all reported the same way as enum variants.
+
+use sorted::sorted;
+
+#[sorted]
+use std::collections::{HashMap, BTreeMap};
+
+#[sorted]
+pub struct Config {
+    timeout: u64,
+    name: String,
+}
+
+#[sorted]
+impl Config {
+    pub fn timeout(&self) -> u64 {
+        self.timeout
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[sorted]
+pub trait Describe {
+    fn summary(&self) -> String;
+    fn describe(&self) -> String;
+}
+
+#[sorted]
+pub struct Pair(u8, u8);
+
+#[sorted::check]
+fn build(_: HashMap<u8, u8>, _: BTreeMap<u8, u8>) -> Config {
+    #[sorted]
+    Config {
+        timeout: 30,
+        name: String::new(),
+    }
+}
+
+fn main() {}
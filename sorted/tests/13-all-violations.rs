@@ -0,0 +1,37 @@
+// Every misplaced entry is reported in one go, across all the #[sorted]
+// matches of a function, so a single compile shows everything to fix. Only
+// the first error of a list carries the sorted order. A #[sorted] on a `let`
+// applies to the match it is initialized with.
+
+use sorted::sorted;
+
+#[sorted]
+pub enum Error {
+    Timeout,
+    Closed,
+    Refused,
+    Aborted,
+}
+
+#[sorted::check]
+fn describe(e: &Error) -> (&str, bool) {
+    use self::Error::*;
+
+    #[sorted]
+    let text = match e {
+        Timeout => "timed out",
+        Refused => "refused",
+        Closed => "closed",
+        Aborted => "aborted",
+    };
+
+    #[sorted]
+    match e {
+        Closed => (text, false),
+        Timeout => (text, true),
+        Aborted => (text, false),
+        Refused => (text, true),
+    }
+}
+
+fn main() {}
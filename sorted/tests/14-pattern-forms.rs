@@ -0,0 +1,54 @@
+// Besides paths, match arms may use literal, range, reference, binding and
+// or-patterns:
+//
+//   - literals and ranges sort by value, a range by its start;
+//   - `&pat` and `name @ pat` sort as the pattern inside them;
+//   - the alternatives of an or-pattern must be sorted among themselves, and
+//     the first one places the arm;
+//   - guards are ignored, so the same pattern may appear with several guards.
+
+use sorted::sorted;
+
+#[sorted]
+pub enum Token {
+    Comma,
+    Ident(String),
+    Number(i64),
+    Semi,
+}
+
+#[sorted::check]
+fn classify(n: i32, c: char, token: &Token) -> &'static str {
+    #[sorted]
+    let size = match n {
+        -1 => "minus one",
+        0 => "zero",
+        2..=9 => "small",
+        10 | 20 | 30 => "round",
+        small @ 11..=99 if small % 2 == 0 => "even",
+        11..=99 => "odd",
+        _ => "large",
+    };
+
+    #[sorted]
+    let kind = match c {
+        '0'..='9' => "digit",
+        'A'..='Z' | 'a'..='z' => "letter",
+        _ => "other",
+    };
+
+    #[sorted]
+    match token {
+        &Token::Comma | &Token::Semi => size,
+        Token::Ident(name) if name.is_empty() => kind,
+        Token::Ident(_) => "ident",
+        number @ Token::Number(_) => {
+            let _ = number;
+            "number"
+        }
+    }
+}
+
+fn main() {
+    let _ = classify(4, 'x', &Token::Comma);
+}
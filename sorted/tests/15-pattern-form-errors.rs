@@ -0,0 +1,24 @@
+// Numeric patterns out of order, and or-patterns whose alternatives are not
+// sorted, are reported like any other misplaced arm.
+
+#[sorted::check]
+fn f(n: u8, c: char) -> u8 {
+    #[sorted]
+    let a = match n {
+        0 => 0,
+        10..=19 => 1,
+        2 | 3 => 2,
+        _ => 3,
+    };
+
+    #[sorted]
+    let b = match c {
+        'x' | 'b' => 4,
+        'y' => 5,
+        _ => 6,
+    };
+
+    a + b
+}
+
+fn main() {}
@@ -0,0 +1,74 @@
+// Large enums often fall into logical groups. A #[sorted::section] marker on a
+// variant starts a new group: variants are sorted within each group, but the
+// groups may come in any order. The marker may carry a label for the reader.
+// The same works for struct fields, impl and trait items, and match arms.
+//
+// In a match, a catch-all arm with a guard also ends a section: the arms after
+// it only match what the guard lets through, so they can't move ahead of it.
+
+use sorted::sorted;
+
+#[sorted]
+pub enum Error {
+    Closed,
+    Refused,
+    Timeout,
+    #[sorted::section("parsing")]
+    BadHeader,
+    Truncated,
+    #[sorted::section]
+    Other,
+}
+
+#[sorted]
+pub struct Settings {
+    host: String,
+    port: u16,
+    #[sorted::section]
+    cert: String,
+    key: String,
+}
+
+#[sorted::check]
+fn is_network(e: &Error) -> bool {
+    use self::Error::*;
+
+    #[sorted]
+    match e {
+        Closed | Refused | Timeout => true,
+        #[sorted::section]
+        BadHeader => false,
+        Truncated => false,
+        #[sorted::section]
+        _ => false,
+    }
+}
+
+#[sorted]
+pub enum Grade {
+    A,
+    B,
+    C,
+    D,
+    F,
+}
+
+#[sorted::check]
+fn points(grade: Grade, strict: bool) -> u8 {
+    use self::Grade::*;
+
+    #[sorted]
+    match grade {
+        C => 2,
+        D => 1,
+        _ if strict => 0,
+        A => 4,
+        B => 3,
+        _ => 0,
+    }
+}
+
+fn main() {
+    let _ = is_network(&Error::Closed);
+    assert_eq!(points(Grade::A, true), 0);
+}
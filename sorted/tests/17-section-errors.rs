@@ -0,0 +1,16 @@
+// Sections only lift the order between groups, within a group it is checked
+// as usual.
+
+use sorted::sorted;
+
+#[sorted]
+pub enum Error {
+    Closed,
+    Timeout,
+    Refused,
+    #[sorted::section]
+    Truncated,
+    BadHeader,
+}
+
+fn main() {}
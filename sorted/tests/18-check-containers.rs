@@ -0,0 +1,96 @@
+// #[sorted::check] also goes on impl blocks, trait impls, traits and inline
+// modules, checking every #[sorted] match in all of their functions, including
+// those inside closures and async blocks. One annotation per module is enough.
+
+use sorted::sorted;
+
+#[sorted]
+pub enum Shape {
+    Circle,
+    Square,
+    Triangle,
+}
+
+#[sorted::check]
+mod geometry {
+    use super::Shape::{self, *};
+
+    pub fn sides(shape: &Shape) -> u8 {
+        #[sorted]
+        match shape {
+            Circle => 0,
+            Square => 4,
+            Triangle => 3,
+        }
+    }
+
+    pub fn names(shapes: &[Shape]) -> Vec<&'static str> {
+        shapes
+            .iter()
+            .map(|shape| {
+                #[sorted]
+                match shape {
+                    Circle => "circle",
+                    Square => "square",
+                    Triangle => "triangle",
+                }
+            })
+            .collect()
+    }
+
+    pub async fn is_round(shape: &Shape) -> bool {
+        async {
+            #[sorted]
+            match shape {
+                Circle => true,
+                Square | Triangle => false,
+            }
+        }
+        .await
+    }
+}
+
+#[sorted::check]
+impl Shape {
+    pub fn corners(&self) -> u8 {
+        #[sorted]
+        match self {
+            Shape::Circle => 0,
+            Shape::Square => 4,
+            Shape::Triangle => 3,
+        }
+    }
+}
+
+#[sorted::check]
+impl std::fmt::Display for Shape {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        #[sorted]
+        let name = match self {
+            Shape::Circle => "circle",
+            Shape::Square => "square",
+            Shape::Triangle => "triangle",
+        };
+        f.write_str(name)
+    }
+}
+
+#[sorted::check]
+pub trait Describe {
+    fn shape(&self) -> Shape;
+
+    fn describe(&self) -> &'static str {
+        #[sorted]
+        match self.shape() {
+            Shape::Circle => "round",
+            _ => "angular",
+        }
+    }
+}
+
+fn main() {
+    let _ = geometry::sides(&Shape::Circle);
+    let _ = geometry::names(&[Shape::Square]);
+    let _ = geometry::is_round(&Shape::Triangle);
+    let _ = Shape::Circle.corners();
+}
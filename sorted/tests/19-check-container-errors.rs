@@ -0,0 +1,28 @@
+// Misplaced arms are found throughout the annotated module, and the attribute
+// is rejected on items it has nothing to look into.
+
+#[sorted::check]
+mod colors {
+    pub enum Color {
+        Blue,
+        Green,
+        Red,
+    }
+
+    pub fn warm(color: &Color) -> bool {
+        let check = |color: &Color| {
+            #[sorted]
+            match color {
+                Color::Red => true,
+                Color::Blue => false,
+                Color::Green => false,
+            }
+        };
+        check(color)
+    }
+}
+
+#[sorted::check]
+pub struct NotAFunction;
+
+fn main() {}
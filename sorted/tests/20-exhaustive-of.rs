@@ -0,0 +1,53 @@
+// With #[sorted(exhaustive_of = "Enum")], a match that ends in a catch-all
+// still has to name every variant of the enum in an arm without a guard. This
+// catches the variant added to the enum but forgotten in the match, which the
+// catch-all would otherwise swallow. The compiler does the checking, on a copy
+// of the match without the catch-all, so the missing variants are reported
+// just as for any non-exhaustive match.
+//
+// A catch-all arm, `_` or a plain lowercase binding, has to come last.
+
+use sorted::sorted;
+
+#[sorted]
+#[derive(Clone, Copy)]
+pub enum Level {
+    Debug,
+    Error,
+    Info,
+    Warn,
+}
+
+#[sorted::check]
+fn is_loud(level: Level, verbose: bool) -> bool {
+    use self::Level::*;
+
+    #[sorted(exhaustive_of = "Level")]
+    match level {
+        Debug if verbose => true,
+        Debug => false,
+        Error | Warn => true,
+        Info => verbose,
+        #[allow(unreachable_patterns)]
+        _ => unreachable!(),
+    }
+}
+
+#[sorted::check]
+fn label(level: &Level) -> &'static str {
+    #[sorted(exhaustive_of = "Level")]
+    let label = match level {
+        &Level::Debug => "debug",
+        Level::Error => "error",
+        Level::Info => "info",
+        Level::Warn => "warn",
+        #[allow(unreachable_patterns)]
+        other => label(other),
+    };
+    label
+}
+
+fn main() {
+    let _ = is_loud(Level::Info, false);
+    let _ = label(&Level::Warn);
+}
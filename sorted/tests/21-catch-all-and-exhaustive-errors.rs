@@ -0,0 +1,46 @@
+// A catch-all in the middle of a match hides the arms after it, and with
+// exhaustive_of every variant the arms leave to the catch-all is reported.
+
+use sorted::sorted;
+
+#[sorted]
+pub enum Level {
+    Debug,
+    Error,
+    Info,
+    Warn,
+}
+
+#[sorted::check]
+fn is_loud(level: Level) -> bool {
+    use self::Level::*;
+
+    #[sorted]
+    match level {
+        Debug => false,
+        _ => true,
+        Info => false,
+    }
+}
+
+#[sorted::check]
+fn rank(level: Level) -> u8 {
+    #[sorted]
+    match level {
+        Level::Debug => 0,
+        other => 1,
+        Level::Warn => 2,
+    }
+}
+
+#[sorted::check]
+fn is_error(level: Level, strict: bool) -> bool {
+    #[sorted(exhaustive_of = "Level")]
+    match level {
+        Level::Error => true,
+        Level::Warn if strict => true,
+        _ => false,
+    }
+}
+
+fn main() {}
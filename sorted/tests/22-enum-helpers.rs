@@ -0,0 +1,60 @@
+// Since #[sorted] has checked the variant order, it can also generate code
+// that relies on it:
+//
+//   - `names` adds `VARIANTS`, the variant names in sorted order, and
+//     `name()`;
+//   - `from_name` adds `from_name()`, a binary search over the names, along
+//     with what `names` adds;
+//   - `ord` implements `PartialOrd` and `Ord` by the names of the variants,
+//     compared as strings whatever order is checked.
+//
+// `from_name` and `ord` need variants without fields.
+
+use sorted::sorted;
+
+#[sorted(from_name, ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+    Delete,
+    Get,
+    Post,
+    Put,
+}
+
+#[sorted(names, order = "natural")]
+pub enum Frame {
+    Data(Vec<u8>),
+    Http2 { stream: u32 },
+    Http10,
+    Ping,
+}
+
+#[sorted(ord, order = "reverse")]
+#[derive(PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+fn main() {
+    assert_eq!(Method::VARIANTS, ["Delete", "Get", "Post", "Put"]);
+    assert_eq!(Method::Post.name(), "Post");
+    assert_eq!(Method::from_name("Put"), Some(Method::Put));
+    assert_eq!(Method::from_name("Delete"), Some(Method::Delete));
+    assert_eq!(Method::from_name("Patch"), None);
+
+    for pair in Method::VARIANTS.windows(2) {
+        let a = Method::from_name(pair[0]).unwrap();
+        let b = Method::from_name(pair[1]).unwrap();
+        assert!(a < b);
+    }
+    let mut methods = vec![Method::Put, Method::Delete, Method::Post];
+    methods.sort();
+    assert_eq!(methods, [Method::Delete, Method::Post, Method::Put]);
+
+    assert!(Direction::Down < Direction::Up);
+
+    assert_eq!(Frame::VARIANTS, ["Data", "Http2", "Http10", "Ping"]);
+    assert_eq!(Frame::Http2 { stream: 1 }.name(), "Http2");
+    assert_eq!(Frame::Data(Vec::new()).name(), "Data");
+}
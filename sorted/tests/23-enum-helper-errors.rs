@@ -0,0 +1,24 @@
+// The generated helpers only go on enums, and `from_name` and `ord` only on
+// enums whose variants have no fields.
+
+use sorted::sorted;
+
+#[sorted(from_name)]
+pub enum Shape {
+    Circle(f64),
+    Square(f64),
+}
+
+#[sorted(names)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[sorted(names, display)]
+pub enum Color {
+    Blue,
+    Red,
+}
+
+fn main() {}
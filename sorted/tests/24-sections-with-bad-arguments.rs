@@ -0,0 +1,28 @@
+// When #[sorted] rejects its arguments or a discriminant, the item is still
+// emitted so that the rest of the crate compiles, but without its section
+// markers. Only the real error is reported.
+
+use sorted::sorted;
+
+#[sorted(order = "bogus")]
+pub enum Error {
+    Closed,
+    #[sorted::section]
+    BadHeader,
+}
+
+#[sorted(order = "discriminant")]
+pub enum Code {
+    Ok = 0,
+    #[sorted::section]
+    Failed = 1 << 4,
+}
+
+#[sorted(unknown)]
+pub struct Settings {
+    host: String,
+    #[sorted::section]
+    cert: String,
+}
+
+fn main() {}
@@ -9,4 +9,20 @@ fn tests() {
     t.compile_fail("tests/06-pattern-path.rs");
     t.compile_fail("tests/07-unrecognized-pattern.rs");
     t.pass("tests/08-underscore.rs");
+    t.pass("tests/09-sort-orders.rs");
+    t.compile_fail("tests/10-sort-order-errors.rs");
+    t.pass("tests/11-sorted-items.rs");
+    t.compile_fail("tests/12-sorted-item-errors.rs");
+    t.compile_fail("tests/13-all-violations.rs");
+    t.pass("tests/14-pattern-forms.rs");
+    t.compile_fail("tests/15-pattern-form-errors.rs");
+    t.pass("tests/16-sections.rs");
+    t.compile_fail("tests/17-section-errors.rs");
+    t.pass("tests/18-check-containers.rs");
+    t.compile_fail("tests/19-check-container-errors.rs");
+    t.pass("tests/20-exhaustive-of.rs");
+    t.compile_fail("tests/21-catch-all-and-exhaustive-errors.rs");
+    t.pass("tests/22-enum-helpers.rs");
+    t.compile_fail("tests/23-enum-helper-errors.rs");
+    t.compile_fail("tests/24-sections-with-bad-arguments.rs");
 }